@@ -3,13 +3,11 @@ use std::sync::Arc;
 use anyhow::Result;
 use async_graphql::MaybeUndefined::{Null, Undefined, Value};
 use async_trait::async_trait;
-use derive_new::new;
 #[cfg(test)]
 use mockall::automock;
-use nakago::{provider, Inject, Provider};
 use nakago_axum::utils::{ManyResponse, Ordering};
-use nakago_derive::Provider;
-use nakago_sea_orm::DatabaseConnection;
+use nakago_derive::Inject;
+use nakago_sea_orm::Connection;
 use sea_orm::{entity::*, query::*, EntityTrait};
 
 use crate::domains::shows::model as show_model;
@@ -56,10 +54,12 @@ pub trait Service: Sync + Send {
 }
 
 /// The default `Service` struct.
-#[derive(new)]
+#[derive(Inject)]
+#[inject(provider = Provide, boxed = dyn Service)]
 pub struct DefaultService {
-    /// The SeaOrm database connection
-    db: Arc<DatabaseConnection>,
+    /// The database Connection
+    #[inject]
+    db: Arc<Connection>,
 }
 
 #[async_trait]
@@ -270,22 +270,11 @@ impl Service for DefaultService {
     }
 }
 
-/// Provide the Service
-#[derive(Default)]
-pub struct Provide {}
-
-#[Provider]
-#[async_trait]
-impl Provider<Box<dyn Service>> for Provide {
-    async fn provide(self: Arc<Self>, i: Inject) -> provider::Result<Arc<Box<dyn Service>>> {
-        let db = i.get::<DatabaseConnection>().await?;
-
-        Ok(Arc::new(Box::new(DefaultService::new(db))))
-    }
-}
-
 #[cfg(test)]
 pub(crate) mod test {
+    use nakago::{provider, Inject, Provider};
+    use nakago_derive::Provider;
+
     use super::*;
 
     /// Provide the Mocked Service for testing
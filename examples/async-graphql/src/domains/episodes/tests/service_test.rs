@@ -3,7 +3,7 @@ use async_graphql::MaybeUndefined::Undefined;
 use fake::{Fake, Faker};
 use nakago::Inject;
 use nakago_axum::utils::ManyResponse;
-use nakago_sea_orm::{connection, DatabaseConnection};
+use nakago_sea_orm::{connection, Connection, DatabaseConnection};
 use pretty_assertions::assert_eq;
 use sea_orm::{DatabaseBackend, MockDatabase, MockExecResult, Transaction, Value};
 
@@ -23,6 +23,9 @@ async fn setup(db: MockDatabase) -> nakago::Result<Inject> {
     i.provide::<DatabaseConnection>(connection::ProvideMock::new(db))
         .await?;
 
+    i.provide::<Connection>(connection::ProvidePool::default())
+        .await?;
+
     i.provide::<Box<dyn Service>>(service::Provide::default())
         .await?;
 
@@ -51,6 +54,8 @@ async fn test_episodes_service_get_success() -> Result<()> {
     // Destroy the service to clean up the DB reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, Some(episode.clone()));
@@ -90,6 +95,8 @@ async fn test_episodes_service_get_with_related() -> Result<()> {
     // Destroy the service to clean up the DB reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, Some(episode.clone()));
@@ -148,6 +155,8 @@ async fn test_episodes_service_get_many() -> Result<()> {
     // Destroy the service to clean up the DB reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(
@@ -217,6 +226,8 @@ async fn test_episodes_service_get_many_with_related() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(
@@ -302,6 +313,8 @@ async fn test_episodes_service_get_many_pagination() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(
@@ -394,6 +407,8 @@ async fn test_episodes_service_get_many_pagination_with_related() -> Result<()>
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(
@@ -463,6 +478,8 @@ async fn test_episodes_service_create() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, episode);
@@ -519,6 +536,8 @@ async fn test_episodes_service_create_with_related() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, episode);
@@ -586,6 +605,8 @@ async fn test_episodes_service_update() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, updated.clone());
@@ -649,6 +670,8 @@ async fn test_episodes_service_update_with_related() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, updated.clone());
@@ -699,6 +722,8 @@ async fn test_episodes_service_delete() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     // Check the transaction log
@@ -3,7 +3,7 @@ use async_graphql::MaybeUndefined::Undefined;
 use fake::{Fake, Faker};
 use nakago::Inject;
 use nakago_axum::utils::ManyResponse;
-use nakago_sea_orm::connection;
+use nakago_sea_orm::{connection, Connection};
 use pretty_assertions::assert_eq;
 use sea_orm::{DatabaseBackend, DatabaseConnection, MockDatabase, MockExecResult, Transaction};
 
@@ -23,6 +23,9 @@ async fn setup(db: MockDatabase) -> nakago::Result<Inject> {
     i.provide::<DatabaseConnection>(connection::ProvideMock::new(db))
         .await?;
 
+    i.provide::<Connection>(connection::ProvidePool::default())
+        .await?;
+
     i.provide::<Box<dyn Service>>(service::Provide::default())
         .await?;
 
@@ -52,6 +55,8 @@ async fn test_profiles_service_get() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, Some(profile.clone().into()));
@@ -92,6 +97,8 @@ async fn test_profiles_service_get_with_related() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, Some(profile.clone().into_profile_with_user(user)));
@@ -155,6 +162,8 @@ async fn test_profiles_service_get_many() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(
@@ -229,6 +238,8 @@ async fn test_profiles_service_get_many_with_related() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(
@@ -322,6 +333,8 @@ async fn test_profiles_service_get_many_pagination() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     let data: ProfileList = profiles.into();
@@ -421,6 +434,8 @@ async fn test_profiles_service_get_many_pagination_with_related() -> Result<()>
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(
@@ -492,6 +507,8 @@ async fn test_profiles_service_create() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, profile.clone().into());
@@ -552,6 +569,8 @@ async fn test_profiles_service_create_with_related() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, profile.clone().into_profile_with_user(user.clone()));
@@ -624,6 +643,8 @@ async fn test_profiles_service_update() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, updated.clone().into());
@@ -690,6 +711,8 @@ async fn test_profiles_service_update_with_related() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, updated.clone().into_profile_with_user(user.clone()));
@@ -741,6 +764,8 @@ async fn test_profiles_service_delete() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     // Check the transaction log
@@ -2,12 +2,10 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use async_trait::async_trait;
-use derive_new::new;
 #[cfg(test)]
 use mockall::automock;
-use nakago::{provider, Inject, Provider};
-use nakago_derive::Provider;
-use nakago_sea_orm::DatabaseConnection;
+use nakago_derive::Inject;
+use nakago_sea_orm::{Connection, Transaction};
 use sea_orm::{entity::*, query::*, Condition, EntityTrait};
 
 use super::model::{self, CreateRoleGrantInput, RoleGrant};
@@ -28,13 +26,31 @@ pub trait Service: Sync + Send {
 
     /// Delete an existing `RoleGrant`
     async fn delete(&self, id: &str) -> Result<()>;
+
+    /// Get a copy of the Service that uses the given request Transaction
+    fn with_transaction(&self, txn: Transaction) -> Box<dyn Service>;
 }
 
 /// The default `Service` struct.
-#[derive(new)]
+#[derive(Inject)]
+#[inject(provider = Provide, boxed = dyn Service)]
 pub struct DefaultService {
-    /// The SeaOrm database connection
-    db: Arc<DatabaseConnection>,
+    /// The database Connection
+    #[inject]
+    db: Arc<Connection>,
+
+    /// The request Transaction, if the Service is scoped to one
+    txn: Option<Transaction>,
+}
+
+impl DefaultService {
+    // The request Transaction if there is one, or the shared connection pool
+    fn conn(&self) -> Connection {
+        match &self.txn {
+            Some(txn) => self.db.with_transaction(txn.clone()),
+            None => (*self.db).clone(),
+        }
+    }
 }
 
 #[async_trait]
@@ -42,7 +58,7 @@ impl Service for DefaultService {
     async fn get(&self, id: &str) -> Result<Option<RoleGrant>> {
         let query = model::Entity::find_by_id(id.to_owned());
 
-        let role_grant = query.one(&*self.db).await?;
+        let role_grant = query.one(&self.conn()).await?;
 
         Ok(role_grant)
     }
@@ -56,7 +72,7 @@ impl Service for DefaultService {
 
         let role_grants = model::Entity::find()
             .filter(condition)
-            .all(&*self.db)
+            .all(&self.conn())
             .await?;
 
         Ok(role_grants)
@@ -70,7 +86,7 @@ impl Service for DefaultService {
             resource_id: Set(input.resource_id.clone()),
             ..Default::default()
         }
-        .insert(&*self.db)
+        .insert(&self.conn())
         .await?;
 
         let created: RoleGrant = role_grant;
@@ -80,26 +96,19 @@ impl Service for DefaultService {
 
     async fn delete(&self, id: &str) -> Result<()> {
         let role_grant = model::Entity::find_by_id(id.to_owned())
-            .one(&*self.db)
+            .one(&self.conn())
             .await?
             .ok_or_else(|| anyhow!("Unable to find RoleGrant with id: {}", id))?;
 
-        let _result = role_grant.delete(&*self.db).await?;
+        let _result = role_grant.delete(&self.conn()).await?;
 
         Ok(())
     }
-}
-
-/// Provide the Service
-#[derive(Default)]
-pub struct Provide {}
-
-#[Provider]
-#[async_trait]
-impl Provider<Box<dyn Service>> for Provide {
-    async fn provide(self: Arc<Self>, i: Inject) -> provider::Result<Arc<Box<dyn Service>>> {
-        let db = i.get::<DatabaseConnection>().await?;
 
-        Ok(Arc::new(Box::new(DefaultService::new(db))))
+    fn with_transaction(&self, txn: Transaction) -> Box<dyn Service> {
+        Box::new(DefaultService {
+            db: self.db.clone(),
+            txn: Some(txn),
+        })
     }
 }
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use fake::{Fake, Faker};
 use nakago::Inject;
-use nakago_sea_orm::connection;
+use nakago_sea_orm::{connection, Connection};
 use pretty_assertions::assert_eq;
 use sea_orm::{DatabaseBackend, DatabaseConnection, MockDatabase, MockExecResult, Transaction};
 
@@ -19,6 +19,9 @@ async fn setup(db: MockDatabase) -> nakago::Result<Inject> {
     i.provide::<DatabaseConnection>(connection::ProvideMock::new(db))
         .await?;
 
+    i.provide::<Connection>(connection::ProvidePool::default())
+        .await?;
+
     i.provide::<Box<dyn Service>>(service::Provide::default())
         .await?;
 
@@ -50,6 +53,8 @@ async fn test_role_grants_service_get() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, Some(grant));
@@ -99,6 +104,8 @@ async fn test_role_grants_service_create() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, grant);
@@ -150,6 +157,8 @@ async fn test_role_grants_service_delete() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     // Check the transaction log
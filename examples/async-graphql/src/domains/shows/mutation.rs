@@ -57,6 +57,7 @@ use async_graphql::{Context, Object, Result};
 use derive_new::new;
 use hyper::StatusCode;
 use nakago_async_graphql::utils::{as_graphql_error, graphql_error};
+use nakago_sea_orm::Transaction;
 use oso::Oso;
 
 use crate::domains::{
@@ -71,6 +72,24 @@ pub struct ShowsMutation {
     role_grants: Arc<Box<dyn role_grants::Service>>,
 }
 
+impl ShowsMutation {
+    // Scope the Service to the request Transaction, if the GraphQL handler opted in to one
+    fn service(&self, ctx: &Context<'_>) -> Arc<Box<dyn Service>> {
+        match ctx.data_opt::<Transaction>() {
+            Some(txn) => Arc::new(self.service.with_transaction(txn.clone())),
+            None => self.service.clone(),
+        }
+    }
+
+    // Scope the RoleGrants Service to the request Transaction in the same way
+    fn role_grants(&self, ctx: &Context<'_>) -> Arc<Box<dyn role_grants::Service>> {
+        match ctx.data_opt::<Transaction>() {
+            Some(txn) => Arc::new(self.role_grants.with_transaction(txn.clone())),
+            None => self.role_grants.clone(),
+        }
+    }
+}
+
 /// Mutations for the Show model
 #[Object]
 impl ShowsMutation {
@@ -81,16 +100,17 @@ impl ShowsMutation {
         input: CreateShowInput,
     ) -> Result<MutateShowResult> {
         let user = ctx.data_unchecked::<Option<User>>();
+        let service = self.service(ctx);
 
         // Check authorization
         if let Some(user) = user {
-            let show = self.service.create(&input).await.map_err(as_graphql_error(
+            let show = service.create(&input).await.map_err(as_graphql_error(
                 "Error while creating Show",
                 StatusCode::INTERNAL_SERVER_ERROR,
             ))?;
 
             // Grant the Admin role to the creator
-            self.role_grants
+            self.role_grants(ctx)
                 .create(&CreateRoleGrantInput {
                     role_key: "admin".to_string(),
                     user_id: user.id.clone(),
@@ -118,10 +138,10 @@ impl ShowsMutation {
     ) -> Result<MutateShowResult> {
         let user = ctx.data_unchecked::<Option<User>>();
         let oso = ctx.data_unchecked::<Oso>();
+        let service = self.service(ctx);
 
         // Retrieve the existing Show for authorization
-        let existing = self
-            .service
+        let existing = service
             .get(&id)
             .await
             .map_err(as_graphql_error(
@@ -139,14 +159,10 @@ impl ShowsMutation {
             return Err(graphql_error("Unauthorized", StatusCode::UNAUTHORIZED));
         }
 
-        let show = self
-            .service
-            .update(&id, &input)
-            .await
-            .map_err(as_graphql_error(
-                "Error while updating Show",
-                StatusCode::INTERNAL_SERVER_ERROR,
-            ))?;
+        let show = service.update(&id, &input).await.map_err(as_graphql_error(
+            "Error while updating Show",
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ))?;
 
         Ok(MutateShowResult { show: Some(show) })
     }
@@ -155,10 +171,10 @@ impl ShowsMutation {
     async fn delete_show(&self, ctx: &Context<'_>, id: String) -> Result<bool> {
         let user = ctx.data_unchecked::<Option<User>>();
         let oso = ctx.data_unchecked::<Oso>();
+        let service = self.service(ctx);
 
         // Retrieve the existing Show for authorization
-        let existing = self
-            .service
+        let existing = service
             .get(&id)
             .await
             .map_err(as_graphql_error(
@@ -176,7 +192,7 @@ impl ShowsMutation {
             return Err(graphql_error("Unauthorized", StatusCode::UNAUTHORIZED));
         }
 
-        self.service.delete(&id).await.map_err(as_graphql_error(
+        service.delete(&id).await.map_err(as_graphql_error(
             "Error while deleting Show",
             StatusCode::INTERNAL_SERVER_ERROR,
         ))?;
@@ -11,6 +11,8 @@ use nakago_axum::utils::{
     Ordering::{self, Asc, Desc},
 };
 use nakago_derive::Provider;
+use nakago_sea_orm::{repository::ids_condition, Filter};
+use sea_orm::{ColumnTrait, QueryFilter, Select};
 
 use super::{
     model::{self, Show},
@@ -62,6 +64,20 @@ pub struct ShowCondition {
     pub ids_in: Option<Vec<String>>,
 }
 
+impl Filter<model::Entity> for ShowCondition {
+    fn apply(self, mut query: Select<model::Entity>) -> Select<model::Entity> {
+        if let Some(title) = self.title {
+            query = query.filter(model::Column::Title.eq(title));
+        }
+
+        if let Some(ids) = self.ids_in {
+            query = query.filter(ids_condition::<model::Entity, _>(ids));
+        }
+
+        query
+    }
+}
+
 /// The available ordering values
 #[derive(Enum, Copy, Clone, Eq, PartialEq)]
 pub enum ShowsOrderBy {
@@ -3,14 +3,12 @@ use std::sync::Arc;
 use anyhow::Result;
 use async_graphql::MaybeUndefined::{Null, Undefined, Value};
 use async_trait::async_trait;
-use derive_new::new;
 #[cfg(test)]
 use mockall::automock;
-use nakago::{provider, Inject, Provider};
-use nakago_axum::utils::{ManyResponse, Ordering};
-use nakago_derive::Provider;
-use nakago_sea_orm::DatabaseConnection;
-use sea_orm::{entity::*, query::*, EntityTrait};
+use nakago_axum::utils::ManyResponse;
+use nakago_derive::Inject;
+use nakago_sea_orm::{Connection, Repository, Transaction};
+use sea_orm::entity::*;
 
 use crate::domains::shows::{
     model::{self, Show},
@@ -45,38 +43,41 @@ pub trait Service: Sync + Send {
 
     /// Delete an existing `Show`
     async fn delete(&self, id: &str) -> Result<()>;
+
+    /// Get a copy of the Service that uses the given request Transaction
+    fn with_transaction(&self, txn: Transaction) -> Box<dyn Service>;
 }
 
 /// The default `Service` struct.
-#[derive(new)]
+#[derive(Inject)]
+#[inject(provider = Provide, boxed = dyn Service)]
 pub struct DefaultService {
-    /// The SeaOrm database connection
-    db: Arc<DatabaseConnection>,
+    /// The database Connection
+    #[inject]
+    db: Arc<Connection>,
+
+    /// The request Transaction, if the Service is scoped to one
+    txn: Option<Transaction>,
+}
+
+impl DefaultService {
+    // A Repository using the request Transaction if there is one, or the shared connection pool
+    fn repo(&self) -> Repository<model::Entity> {
+        match &self.txn {
+            Some(txn) => Repository::new(self.db.with_transaction(txn.clone())),
+            None => Repository::new((*self.db).clone()),
+        }
+    }
 }
 
 #[async_trait]
 impl Service for DefaultService {
     async fn get(&self, id: &str) -> Result<Option<model::Model>> {
-        let query = model::Entity::find_by_id(id.to_owned());
-
-        let show = query.one(&*self.db).await?;
-
-        Ok(show)
+        Ok(self.repo().get(id).await?)
     }
 
     async fn get_by_ids(&self, ids: Vec<String>) -> Result<Vec<Show>> {
-        let mut condition = Condition::any();
-
-        for id in ids {
-            condition = condition.add(model::Column::Id.eq(id.clone()));
-        }
-
-        let shows = model::Entity::find()
-            .filter(condition)
-            .all(&*self.db)
-            .await?;
-
-        Ok(shows)
+        Ok(self.repo().get_by_ids(ids).await?)
     }
 
     async fn get_many(
@@ -86,55 +87,10 @@ impl Service for DefaultService {
         page: Option<u64>,
         page_size: Option<u64>,
     ) -> Result<ManyResponse<Show>> {
-        let page_num = page.unwrap_or(1);
-
-        let mut query = model::Entity::find();
-
-        if let Some(condition) = condition {
-            if let Some(title) = condition.title {
-                query = query.filter(model::Column::Title.eq(title));
-            }
-
-            if let Some(ids) = condition.ids_in {
-                let mut condition = Condition::any();
-
-                for id in ids {
-                    condition = condition.add(model::Column::Id.eq(id.clone()));
-                }
-
-                query = query.filter(condition);
-            }
-        }
-
-        if let Some(order_by) = order_by {
-            for order in order_by {
-                let ordering: Ordering<model::Column> = order.into();
-
-                match ordering {
-                    Ordering::Asc(column) => {
-                        query = query.order_by_asc(column);
-                    }
-                    Ordering::Desc(column) => {
-                        query = query.order_by_desc(column);
-                    }
-                }
-            }
-        }
-
-        let (data, total) = if let Some(page_size) = page_size {
-            let paginator = query.paginate(&*self.db, page_size);
-            let total = paginator.num_items().await?;
-            let data: Vec<Show> = paginator.fetch_page(page_num - 1).await?;
-
-            (data, total)
-        } else {
-            let data: Vec<Show> = query.all(&*self.db).await?;
-            let total = data.len().try_into().unwrap_or(0);
-
-            (data, total)
-        };
-
-        Ok(ManyResponse::new(data, total, page_num, page_size))
+        Ok(self
+            .repo()
+            .get_many(condition, order_by, page, page_size)
+            .await?)
     }
 
     async fn create(&self, input: &CreateShowInput) -> Result<Show> {
@@ -143,21 +99,17 @@ impl Service for DefaultService {
             summary: Set(input.summary.clone()),
             picture: Set(input.picture.clone()),
             ..Default::default()
-        }
-        .insert(&*self.db)
-        .await?;
-
-        let created: Show = show;
+        };
 
-        return Ok(created);
+        Ok(self.repo().create(show).await?)
     }
 
     async fn update(&self, id: &str, input: &UpdateShowInput) -> Result<Show> {
-        let query = model::Entity::find_by_id(id.to_owned());
+        let repo = self.repo();
 
         // Retrieve the existing Show
-        let show = query
-            .one(&*self.db)
+        let show = repo
+            .get(id)
             .await?
             .ok_or_else(|| anyhow!("Unable to find Show with id: {}", id))?;
 
@@ -180,39 +132,34 @@ impl Service for DefaultService {
             Value(value) => show.picture = Set(Some(value.clone())),
         }
 
-        let updated: Show = show.update(&*self.db).await?;
-
-        Ok(updated)
+        Ok(repo.update(show).await?)
     }
 
     async fn delete(&self, id: &str) -> Result<()> {
-        let show = model::Entity::find_by_id(id.to_owned())
-            .one(&*self.db)
+        let repo = self.repo();
+
+        repo.get(id)
             .await?
             .ok_or_else(|| anyhow!("Unable to find Show with id: {}", id))?;
 
-        let _result = show.delete(&*self.db).await?;
+        repo.delete(id).await?;
 
         Ok(())
     }
-}
 
-/// Provide the Service
-#[derive(Default)]
-pub struct Provide {}
-
-#[Provider]
-#[async_trait]
-impl Provider<Box<dyn Service>> for Provide {
-    async fn provide(self: Arc<Self>, i: Inject) -> provider::Result<Arc<Box<dyn Service>>> {
-        let db = i.get::<DatabaseConnection>().await?;
-
-        Ok(Arc::new(Box::new(DefaultService::new(db))))
+    fn with_transaction(&self, txn: Transaction) -> Box<dyn Service> {
+        Box::new(DefaultService {
+            db: self.db.clone(),
+            txn: Some(txn),
+        })
     }
 }
 
 #[cfg(test)]
 pub(crate) mod test {
+    use nakago::{provider, Inject, Provider};
+    use nakago_derive::Provider;
+
     use super::*;
 
     /// Provide the Mocked Service for testing
@@ -3,7 +3,7 @@ use async_graphql::MaybeUndefined;
 use fake::{Fake, Faker};
 use nakago::Inject;
 use nakago_axum::utils::ManyResponse;
-use nakago_sea_orm::connection;
+use nakago_sea_orm::{connection, test::TestDatabase, Connection};
 use pretty_assertions::assert_eq;
 use sea_orm::{
     DatabaseBackend, DatabaseConnection, MockDatabase, MockExecResult, Transaction, Value,
@@ -22,6 +22,9 @@ async fn setup(db: MockDatabase) -> nakago::Result<Inject> {
     i.provide::<DatabaseConnection>(connection::ProvideMock::new(db))
         .await?;
 
+    i.provide::<Connection>(connection::ProvidePool::default())
+        .await?;
+
     i.provide::<Box<dyn Service>>(service::Provide::default())
         .await?;
 
@@ -45,6 +48,8 @@ async fn test_shows_service_get() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, Some(show.clone()));
@@ -93,6 +98,8 @@ async fn test_shows_service_get_many() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(
@@ -165,6 +172,8 @@ async fn test_shows_service_get_many_pagination() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(
@@ -221,6 +230,8 @@ async fn test_shows_service_create() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, show);
@@ -270,6 +281,8 @@ async fn test_shows_service_update() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, updated.clone());
@@ -316,6 +329,8 @@ async fn test_shows_service_delete() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     // Check the transaction log
@@ -337,3 +352,61 @@ async fn test_shows_service_delete() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_shows_service_sqlite() -> Result<()> {
+    let i = Inject::default();
+
+    // An in-memory SQLite database with the columns of the Postgres schema
+    let test_db = TestDatabase::memory().await?;
+    test_db
+        .execute(
+            "CREATE TABLE shows (
+                id TEXT NOT NULL PRIMARY KEY,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                title TEXT NOT NULL,
+                summary TEXT,
+                picture TEXT
+            );
+            INSERT INTO shows (id, created_at, updated_at, title)
+                VALUES ('show-id', '2024-01-01 00:00:00', '2024-01-01 00:00:00', 'Test Show');",
+        )
+        .await?;
+
+    test_db.inject(&i).await?;
+
+    i.provide::<Connection>(connection::ProvidePool::default())
+        .await?;
+
+    i.provide::<Box<dyn Service>>(service::Provide::default())
+        .await?;
+
+    let service = i.get::<Box<dyn Service>>().await?;
+
+    let show = service.get("show-id").await?.expect("no show found");
+    assert_eq!("Test Show", show.title);
+
+    let updated = service
+        .update(
+            "show-id",
+            &UpdateShowInput {
+                title: MaybeUndefined::Value("Updated Show".to_string()),
+                summary: MaybeUndefined::Value("A summary".to_string()),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+    assert_eq!("Updated Show", updated.title);
+    assert_eq!(Some("A summary".to_string()), updated.summary);
+    assert_eq!(
+        vec![updated],
+        service.get_by_ids(vec!["show-id".to_string()]).await?
+    );
+
+    service.delete("show-id").await?;
+    assert_eq!(None, service.get("show-id").await?);
+
+    Ok(())
+}
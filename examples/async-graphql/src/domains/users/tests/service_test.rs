@@ -1,7 +1,7 @@
 use anyhow::Result;
 use fake::{Fake, Faker};
 use nakago::Inject;
-use nakago_sea_orm::connection;
+use nakago_sea_orm::{connection, Connection};
 use pretty_assertions::assert_eq;
 use sea_orm::{DatabaseBackend, DatabaseConnection, MockDatabase, MockExecResult, Transaction};
 
@@ -17,6 +17,9 @@ async fn setup(db: MockDatabase) -> nakago::Result<Inject> {
     i.provide::<DatabaseConnection>(connection::ProvideMock::new(db))
         .await?;
 
+    i.provide::<Connection>(connection::ProvidePool::default())
+        .await?;
+
     i.provide::<Box<dyn Service>>(service::Provide::default())
         .await?;
 
@@ -41,6 +44,8 @@ async fn test_users_service_get() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, Some(user.clone()));
@@ -76,6 +81,8 @@ async fn test_users_service_get_by_username() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, Some(user));
@@ -113,6 +120,8 @@ async fn test_users_service_create() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, user);
@@ -163,6 +172,8 @@ async fn test_users_service_update() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     assert_eq!(result, updated.clone());
@@ -211,6 +222,8 @@ async fn test_users_service_delete() -> Result<()> {
     // Destroy the service to clean up the reference count
     drop(service);
 
+    // The Connection holds a reference to the DatabaseConnection, so it is consumed first
+    drop(i.consume::<Connection>().await?);
     let db = i.eject::<DatabaseConnection>().await?;
 
     // Check the transaction log
@@ -2,6 +2,7 @@ use async_graphql::http::GraphiQLSource;
 use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
 use axum::response::{self, IntoResponse};
 use nakago_axum::{auth::Subject, Inject};
+use nakago_sea_orm::Transaction;
 
 use crate::domains::{graphql, users};
 
@@ -10,6 +11,7 @@ pub async fn resolve(
     Inject(schema): Inject<graphql::Schema>,
     Inject(users): Inject<Box<dyn users::Service>>,
     sub: Subject,
+    txn: Option<Transaction>,
     req: GraphQLRequest,
 ) -> GraphQLResponse {
     // Retrieve the request User, if username is present
@@ -24,9 +26,23 @@ pub async fn resolve(
     };
 
     // Add the Subject and optional User to the context
-    let request = req.into_inner().data(sub).data(user);
+    let mut request = req.into_inner().data(sub).data(user);
 
-    schema.execute(request).await.into()
+    // Mutations opt in to the request Transaction by reading it from the context
+    if let Some(txn) = &txn {
+        request = request.data(txn.clone());
+    }
+
+    let response = schema.execute(request).await;
+
+    // GraphQL errors are sent with a successful status, so the Transaction is marked directly
+    if let Some(txn) = &txn {
+        if response.is_err() {
+            txn.set_rollback_only();
+        }
+    }
+
+    response.into()
 }
 
 /// Handle GraphiQL UI Requests
@@ -1,6 +1,11 @@
-use axum::{routing::get, Router};
+use axum::{
+    middleware::from_fn_with_state,
+    routing::{get, post},
+    Router,
+};
 use nakago::Inject;
 use nakago_axum::{init::trace_layer, State};
+use nakago_sea_orm::{transaction, DatabaseConnection};
 use nakago_ws::{connections, controller};
 
 use crate::events::{handler, session::Session};
@@ -22,11 +27,17 @@ pub async fn load(i: &Inject) -> nakago::Result<()> {
 }
 
 /// Initialize the HTTP router
-pub fn init(i: &Inject) -> Router {
-    Router::new()
+pub async fn init(i: &Inject) -> nakago::Result<Router> {
+    let db = i.get::<DatabaseConnection>().await?;
+
+    // Each GraphQL request gets a Transaction that mutations can opt in to. It is only begun when
+    // a mutation first uses it, so queries don't hold an extra pooled connection.
+    let resolve = post(graphql::resolve).layer(from_fn_with_state(db, transaction::middleware));
+
+    Ok(Router::new()
         .layer(trace_layer())
         .route("/health", get(health::health_check))
-        .route("/graphql", get(graphql::graphiql).post(graphql::resolve))
+        .route("/graphql", get(graphql::graphiql).merge(resolve))
         .route("/events", get(events::handle))
-        .with_state(State::new(i.clone()))
+        .with_state(State::new(i.clone())))
 }
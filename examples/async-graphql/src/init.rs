@@ -1,7 +1,7 @@
-use std::path::PathBuf;
-
 use nakago::{self, Inject};
 use nakago_axum::auth::{jwks, validator, Empty, JWKSet, Validator};
+use nakago_figment::Args;
+use nakago_sea_orm::Connection;
 use oso::Oso;
 use sea_orm::DatabaseConnection;
 
@@ -12,7 +12,7 @@ use crate::{
 };
 
 /// Create a dependency injection container for the top-level application
-pub async fn app(config_path: Option<PathBuf>) -> nakago::Result<Inject> {
+pub async fn app(args: Args<Config>) -> nakago::Result<Inject> {
     let i = Inject::default();
 
     i.provide::<JWKSet<Empty>>(jwks::Provide::<Config>::default())
@@ -24,15 +24,25 @@ pub async fn app(config_path: Option<PathBuf>) -> nakago::Result<Inject> {
     i.provide::<DatabaseConnection>(nakago_sea_orm::connection::Provide::<Config>::new())
         .await?;
 
+    // Services use the Connection, which records a tracing span for each statement
+    i.provide::<Connection>(nakago_sea_orm::instrument::Provide::<Config>::new())
+        .await?;
+
     i.provide::<Oso>(ProvideOso::default()).await?;
 
-    // Add config loaders before the Config is initialized
-    nakago_axum::config::add_default_loaders(&i).await?;
-    nakago_sea_orm::config::add_default_loaders(&i).await?;
+    // Add the env and secret file config loaders before the Config is initialized
+    nakago_figment::env::add_loader::<Config>(&i).await?;
+    nakago_figment::secrets::add_loader::<Config>(&i, None).await?;
 
     // Initialize the Config
     nakago_figment::Init::<Config>::default()
-        .maybe_with_path(config_path)
+        .with_args(&args)
+        .init(&i)
+        .await?;
+
+    // Check the migrations, applying pending ones if the database `migrate` flag is set
+    #[cfg(feature = "migrate")]
+    nakago_sea_orm::migrate::Init::<crate::migrations::Migrator, Config>::default()
         .init(&i)
         .await?;
 
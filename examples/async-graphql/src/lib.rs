@@ -19,6 +19,10 @@ pub mod events;
 /// Authorization
 pub mod authz;
 
+/// Database migrations
+#[cfg(feature = "migrate")]
+pub mod migrations;
+
 /// Error macros
 #[macro_use]
 extern crate anyhow;
@@ -1,12 +1,13 @@
 //! The main entry point for the async-graphql example.
 #![forbid(unsafe_code)]
 
-use std::{panic, path::PathBuf};
+use std::{ffi::OsString, panic};
 
 use http::router;
 use log::info;
 use nakago_axum::init::{handle_panic, rust_log_subscriber, Listener};
-use pico_args::{Arguments, Error};
+use nakago_figment::{schema, Args, Report};
+use pico_args::Arguments;
 
 mod authz;
 mod config;
@@ -25,47 +26,81 @@ const HELP: &str = "\
 Usage: async-graphql [OPTIONS]
 
 Options:
-  -c, --config <FILE>  Sets a custom config file path
+  --print-config       Print the effective config and where each value came from
+  --json               Print the effective config as JSON
+  --print-schema       Print the JSON Schema for the config
+  --print-example      Print a commented example config.toml
   -h, --help           Print help
 ";
 
 #[derive(Debug)]
-struct Args {
-    /// Prints the usage menu
-    help: bool,
+struct Options {
+    /// Prints the effective config
+    print_config: bool,
 
-    /// Sets a custom config file path
-    config_path: Option<PathBuf>,
-}
+    /// Prints the effective config as JSON
+    json: bool,
 
-impl Args {
-    pub fn parse() -> Result<Args, Error> {
-        let mut pargs = Arguments::from_env();
+    /// Prints the JSON Schema for the config
+    print_schema: bool,
 
-        let args = Args {
-            help: pargs.contains(["-h", "--help"]),
-            config_path: pargs.opt_value_from_str(["-c", "--config"])?,
-        };
+    /// Prints a commented example config
+    print_example: bool,
+}
 
-        Ok(args)
+impl Options {
+    /// Parse the arguments that aren't handled by the Config Args
+    pub fn parse(rest: Vec<OsString>) -> Options {
+        let mut pargs = Arguments::from_vec(rest);
+
+        Options {
+            print_config: pargs.contains("--print-config"),
+            json: pargs.contains("--json"),
+            print_schema: pargs.contains("--print-schema"),
+            print_example: pargs.contains("--print-example"),
+        }
     }
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let args = Args::parse()?;
+    let args = Args::<Config>::parse()?;
+    let options = Options::parse(args.rest.clone());
 
     if args.help {
         println!("{}", HELP);
+        println!("{}", Args::<Config>::help());
+        return Ok(());
+    }
+
+    if options.print_schema {
+        println!("{}", schema::schema_json::<Config>());
+        return Ok(());
+    }
+
+    if options.print_example {
+        print!("{}", schema::example::<Config>());
         return Ok(());
     }
 
     panic::set_hook(Box::new(handle_panic));
     rust_log_subscriber();
 
-    let i = init::app(args.config_path).await?;
+    let i = init::app(args).await?;
+
+    if options.print_config {
+        let report = i.get::<Report>().await?;
+
+        if options.json {
+            println!("{}", serde_json::to_string_pretty(&*report)?);
+        } else {
+            print!("{report}");
+        }
+
+        return Ok(());
+    }
 
-    let router = router::init(&i);
+    let router = router::init(&i).await?;
 
     let (server, addr) = Listener::<Config>::default().init(&i, router).await?;
 
@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, DbErr};
+use sea_orm_migration::{MigrationName, MigrationTrait, MigratorTrait, SchemaManager};
+
+// Embed a migration file by name
+macro_rules! sql {
+    ($name:literal) => {
+        Box::new(Sql {
+            name: $name,
+            sql: include_str!(concat!("../migrations/", $name, ".sql")),
+        })
+    };
+}
+
+/// The SQL migrations in the `migrations` directory, in order. Each is named after its file.
+///
+/// These are the same files that the SQLx CLI runs, but the applied migrations are tracked in a
+/// separate table, so a database should be migrated with one or the other.
+pub struct Migrator;
+
+#[async_trait]
+impl MigratorTrait for Migrator {
+    fn migrations() -> Vec<Box<dyn MigrationTrait>> {
+        vec![
+            sql!("20220409152743_gen_random_ulid"),
+            sql!("20220409153117_sync_updated_at"),
+            sql!("20220409153224_users_and_profiles"),
+            sql!("20220409153240_role_grants"),
+            sql!("20220409153302_role_triggers"),
+            sql!("20220409204417_shows_and_episodes"),
+            sql!("20220411175104_messages"),
+        ]
+    }
+}
+
+// A migration that runs the statements from a SQL file. The files don't include a way to undo
+// them, so they can't be rolled back.
+struct Sql {
+    name: &'static str,
+    sql: &'static str,
+}
+
+impl MigrationName for Sql {
+    fn name(&self) -> &str {
+        self.name
+    }
+}
+
+#[async_trait]
+impl MigrationTrait for Sql {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(self.sql)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Migration(format!(
+            "{} can't be rolled back",
+            self.name
+        )))
+    }
+}
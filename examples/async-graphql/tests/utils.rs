@@ -7,6 +7,7 @@ use axum::http::HeaderValue;
 use fake::{Fake, Faker};
 use futures_util::{stream::SplitStream, Future, SinkExt, StreamExt};
 use nakago_axum::auth::{validator, Validator};
+use nakago_figment::Args;
 use tokio::{net::TcpStream, time::timeout};
 use tokio_tungstenite::{
     connect_async, tungstenite,
@@ -38,15 +39,16 @@ impl Deref for Utils {
 
 impl Utils {
     pub async fn init() -> Result<Self> {
-        let config_path = std::env::var("CONFIG_PATH_ASYNC_GRAPHQL")
-            .unwrap_or_else(|_| "examples/async-graphql/config.test.toml".to_string());
+        let config_path = std::env::var("CONFIG_PATH_ASYNC_GRAPHQL").unwrap_or_else(|_| {
+            concat!(env!("CARGO_MANIFEST_DIR"), "/config.test.toml").to_string()
+        });
 
-        let i = init::app(Some(config_path.clone().into())).await?;
+        let i = init::app(Args::default().with_config(config_path.clone().into())).await?;
 
         i.replace_with::<Box<dyn Validator>>(validator::ProvideUnverified::default())
             .await?;
 
-        let router = router::init(&i);
+        let router = router::init(&i).await?;
 
         let utils = nakago_async_graphql::test::Utils::init(i, "/", "/graphql", router).await?;
 
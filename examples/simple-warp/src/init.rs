@@ -1,6 +1,5 @@
-use std::path::PathBuf;
-
 use nakago::{Inject, Result};
+use nakago_figment::Args;
 use nakago_warp::{
     auth::{jwks, validator, Jwks, Validator},
     config,
@@ -9,7 +8,7 @@ use nakago_warp::{
 use crate::config::Config;
 
 /// Create a dependency injection container for the top-level application
-pub async fn app(config_path: Option<PathBuf>) -> Result<Inject> {
+pub async fn app(args: Args<Config>) -> Result<Inject> {
     let i = Inject::default();
 
     // Dependencies
@@ -25,7 +24,7 @@ pub async fn app(config_path: Option<PathBuf>) -> Result<Inject> {
 
     // Initialize the Config
     nakago_figment::Init::<Config>::default()
-        .maybe_with_path(config_path)
+        .with_args(&args)
         .init(&i)
         .await?;
 
@@ -1,13 +1,11 @@
 //! The main entry point for the simple-warp example.
 #![forbid(unsafe_code)]
 
-use std::path::PathBuf;
-
 use config::Config;
 use http::router;
 use log::info;
+use nakago_figment::Args;
 use nakago_warp::init::Listener;
-use pico_args::{Arguments, Error};
 
 mod config;
 mod http;
@@ -17,42 +15,20 @@ const HELP: &str = "\
 Usage: simple-warp [OPTIONS]
 
 Options:
-  -c, --config <FILE>  Sets a custom config file path
   -h, --help           Print help
 ";
 
-#[derive(Debug)]
-struct Args {
-    /// Prints the usage menu
-    help: bool,
-
-    /// Sets a custom config file path
-    config_path: Option<PathBuf>,
-}
-
-impl Args {
-    pub fn parse() -> Result<Args, Error> {
-        let mut pargs = Arguments::from_env();
-
-        let args = Args {
-            help: pargs.contains(["-h", "--help"]),
-            config_path: pargs.opt_value_from_str(["-c", "--config"])?,
-        };
-
-        Ok(args)
-    }
-}
-
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let args = Args::parse()?;
+    let args = Args::<Config>::parse()?;
 
     if args.help {
         println!("{}", HELP);
+        println!("{}", Args::<Config>::help());
         return Ok(());
     }
 
-    let i = init::app(args.config_path).await?;
+    let i = init::app(args).await?;
 
     let router = router::init(&i);
 
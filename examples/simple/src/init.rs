@@ -1,15 +1,14 @@
-use std::path::PathBuf;
-
 use nakago::{Inject, Result};
 use nakago_axum::{
     auth::{jwks, validator, Empty, JWKSet, Validator},
     config,
 };
+use nakago_figment::Args;
 
 use crate::config::Config;
 
 /// Create a dependency injection container for the top-level application
-pub async fn app(config_path: Option<PathBuf>) -> Result<Inject> {
+pub async fn app(args: Args<Config>) -> Result<Inject> {
     let i = Inject::default();
 
     i.provide::<JWKSet<Empty>>(jwks::Provide::<Config>::default())
@@ -23,7 +22,7 @@ pub async fn app(config_path: Option<PathBuf>) -> Result<Inject> {
 
     // Initialize the Config
     nakago_figment::Init::<Config>::default()
-        .maybe_with_path(config_path)
+        .with_args(&args)
         .init(&i)
         .await?;
 
@@ -1,13 +1,13 @@
 //! The main entry point for the simple example.
 #![forbid(unsafe_code)]
 
-use std::{panic, path::PathBuf};
+use std::panic;
 
 use config::Config;
 use http::router;
 use log::info;
 use nakago_axum::init::{handle_panic, rust_log_subscriber, Listener};
-use pico_args::{Arguments, Error};
+use nakago_figment::Args;
 
 mod config;
 mod http;
@@ -17,45 +17,23 @@ const HELP: &str = "\
 Usage: simple [OPTIONS]
 
 Options:
-  -c, --config <FILE>  Sets a custom config file path
   -h, --help           Print help
 ";
 
-#[derive(Debug)]
-struct Args {
-    /// Prints the usage menu
-    help: bool,
-
-    /// Sets a custom config file path
-    config_path: Option<PathBuf>,
-}
-
-impl Args {
-    pub fn parse() -> Result<Args, Error> {
-        let mut pargs = Arguments::from_env();
-
-        let args = Args {
-            help: pargs.contains(["-h", "--help"]),
-            config_path: pargs.opt_value_from_str(["-c", "--config"])?,
-        };
-
-        Ok(args)
-    }
-}
-
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let args = Args::parse()?;
+    let args = Args::<Config>::parse()?;
 
     if args.help {
         println!("{}", HELP);
+        println!("{}", Args::<Config>::help());
         return Ok(());
     }
 
     panic::set_hook(Box::new(handle_panic));
     rust_log_subscriber();
 
-    let i = init::app(args.config_path).await?;
+    let i = init::app(args).await?;
 
     let router = router::init(&i);
 
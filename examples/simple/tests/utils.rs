@@ -2,6 +2,7 @@ use std::ops::Deref;
 
 use anyhow::Result;
 use nakago_axum::auth::{validator, Validator};
+use nakago_figment::Args;
 
 use nakago_examples_simple::{http::router, init, Config};
 
@@ -17,10 +18,11 @@ impl Deref for TestUtils {
 
 impl TestUtils {
     pub async fn init() -> Result<Self> {
-        let config_path = std::env::var("CONFIG_PATH_SIMPLE")
-            .unwrap_or_else(|_| "examples/simple/config.test.toml".to_string());
+        let config_path = std::env::var("CONFIG_PATH_SIMPLE").unwrap_or_else(|_| {
+            concat!(env!("CARGO_MANIFEST_DIR"), "/config.test.toml").to_string()
+        });
 
-        let i = init::app(Some(config_path.clone().into())).await?;
+        let i = init::app(Args::default().with_config(config_path.clone().into())).await?;
 
         i.replace_with::<Box<dyn Validator>>(validator::ProvideUnverified::default())
             .await?;
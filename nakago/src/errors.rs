@@ -53,6 +53,13 @@ pub enum Error {
         strong_count: usize,
     },
 
+    /// An error thrown when a Lazy handle is resolved after its container has been dropped
+    #[error("{0} cannot be resolved because the container has been dropped")]
+    Dropped(
+        /// The Key of the entity that could not be resolved
+        Key,
+    ),
+
     /// A generic error for anything else
     #[error("general failure")]
     Any(#[from] Arc<anyhow::Error>),
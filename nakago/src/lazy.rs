@@ -0,0 +1,299 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    fmt::{self, Debug},
+    marker::PhantomData,
+    sync::{Arc, Weak},
+};
+
+use tokio::sync::{OnceCell, RwLock};
+
+use super::{Error, Inject, Injector, Key, Result, Tag};
+
+/// A cheap handle to a Dependency that is resolved from the container on the first `.get().await`
+/// rather than eagerly inside a Provider. This allows Providers to take optional or rarely used
+/// collaborators, and to break mutual references between Dependencies that need each other.
+///
+/// The handle holds a weak reference to the container, so it doesn't keep the container alive and
+/// doesn't interfere with `consume` or `eject`. Awaiting a Lazy Dependency from inside the Provider
+/// for that same Dependency will never resolve, so save the `.get()` for after initialization.
+pub struct Lazy<T: Any + Send + Sync> {
+    container: Weak<RwLock<HashMap<Key, Injector>>>,
+    key: Key,
+    value: OnceCell<Arc<T>>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: Any + Send + Sync> Lazy<T> {
+    fn new(i: &Inject, key: Key) -> Self {
+        Self {
+            container: Arc::downgrade(&i.0),
+            key,
+            value: OnceCell::new(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The Key that this handle will resolve
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+
+    /// Resolve the Dependency from the container the first time this is called, and return the
+    /// cached reference afterwards. Return a NotFound error if the Key isn't present.
+    pub async fn get(&self) -> Result<Arc<T>> {
+        self.value
+            .get_or_try_init(|| async { self.container()?.get_key(self.key.clone()).await })
+            .await
+            .cloned()
+    }
+
+    /// Resolve the Dependency from the container if it exists, caching it once it is found.
+    pub async fn get_opt(&self) -> Result<Option<Arc<T>>> {
+        if let Some(value) = self.value.get() {
+            return Ok(Some(value.clone()));
+        }
+
+        match self.container()?.get_key_opt::<T>(self.key.clone()).await? {
+            Some(value) => Ok(Some(
+                self.value.get_or_init(|| async { value }).await.clone(),
+            )),
+            None => Ok(None),
+        }
+    }
+
+    fn container(&self) -> Result<Inject> {
+        self.container
+            .upgrade()
+            .map(Inject)
+            .ok_or_else(|| Error::Dropped(self.key.clone()))
+    }
+}
+
+impl<T: Any + Send + Sync> Clone for Lazy<T> {
+    fn clone(&self) -> Self {
+        Self {
+            container: self.container.clone(),
+            key: self.key.clone(),
+            value: self.value.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: Any + Send + Sync> Debug for Lazy<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lazy")
+            .field("key", &self.key)
+            .field("resolved", &self.value.initialized())
+            .finish()
+    }
+}
+
+impl Inject {
+    /// Create a Lazy handle for a Dependency that will be resolved on the first `.get().await`.
+    pub fn lazy<T: Any + Send + Sync>(&self) -> Lazy<T> {
+        Lazy::new(self, Key::from_type_id::<T>())
+    }
+
+    /// Create a Lazy handle for a Tagged Dependency that will be resolved on the first
+    /// `.get().await`.
+    pub fn lazy_tag<T: Any + Send + Sync>(&self, tag: &'static Tag<T>) -> Lazy<T> {
+        Lazy::new(self, Key::from_tag(tag))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use fake::Fake;
+    use nakago_derive::Provider;
+
+    use crate::{
+        container::test::{OtherService, TestService},
+        provider::{self, test::TestServiceProvider},
+        Provider,
+    };
+
+    use super::*;
+
+    pub const SERVICE_TAG: Tag<TestService> = Tag::new("lazy::test::Service");
+
+    // A pair of services that refer to each other
+    pub struct Left {
+        right: Lazy<Right>,
+    }
+
+    pub struct Right {
+        left: Lazy<Left>,
+    }
+
+    #[derive(Default)]
+    pub struct LeftProvider {}
+
+    #[Provider(internal)]
+    #[async_trait]
+    impl Provider<Left> for LeftProvider {
+        async fn provide(self: Arc<Self>, i: Inject) -> provider::Result<Arc<Left>> {
+            Ok(Arc::new(Left {
+                right: i.lazy::<Right>(),
+            }))
+        }
+    }
+
+    #[derive(Default)]
+    pub struct RightProvider {}
+
+    #[Provider(internal)]
+    #[async_trait]
+    impl Provider<Right> for RightProvider {
+        async fn provide(self: Arc<Self>, i: Inject) -> provider::Result<Arc<Right>> {
+            // Request the Left service eagerly, which is safe because it only holds a Lazy handle
+            let _left = i.get::<Left>().await?;
+
+            Ok(Arc::new(Right {
+                left: i.lazy::<Left>(),
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lazy_get_success() -> Result<()> {
+        let i = Inject::default();
+
+        let expected: String = fake::uuid::UUIDv4.fake();
+
+        // The handle can be created before the Provider is available
+        let lazy = i.lazy::<TestService>();
+
+        i.provide::<TestService>(TestServiceProvider::new(expected.clone()))
+            .await?;
+
+        let result = lazy.get().await?;
+        assert_eq!(expected, result.id);
+
+        // Subsequent calls return the cached reference
+        let again = lazy.get().await?;
+        assert!(Arc::ptr_eq(&result, &again));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lazy_tag_get_success() -> Result<()> {
+        let i = Inject::default();
+
+        let expected: String = fake::uuid::UUIDv4.fake();
+
+        i.inject_tag(&SERVICE_TAG, TestService::new(expected.clone()))
+            .await?;
+
+        let lazy = i.lazy_tag(&SERVICE_TAG);
+
+        let result = lazy.get().await?;
+        assert_eq!(expected, result.id);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lazy_get_not_found() -> Result<()> {
+        let i = Inject::default();
+
+        let lazy = i.lazy::<TestService>();
+
+        let result = lazy.get().await;
+
+        if let Err(err) = result {
+            assert!(err.to_string().contains("was not found"));
+        } else {
+            panic!("did not return Err as expected")
+        }
+
+        // The handle can still be resolved once the Dependency is available
+        i.inject(TestService::new(fake::uuid::UUIDv4.fake()))
+            .await?;
+
+        assert!(lazy.get_opt().await?.is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lazy_get_opt_not_found() -> Result<()> {
+        let i = Inject::default();
+
+        let result = i.lazy::<OtherService>().get_opt().await?;
+
+        assert!(result.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lazy_container_dropped() -> Result<()> {
+        let i = Inject::default();
+
+        i.inject(TestService::new(fake::uuid::UUIDv4.fake()))
+            .await?;
+
+        let lazy = i.lazy::<TestService>();
+
+        drop(i);
+
+        let result = lazy.get().await;
+
+        if let Err(err) = result {
+            assert_eq!(
+                format!(
+                    "{} cannot be resolved because the container has been dropped",
+                    lazy.key()
+                ),
+                err.to_string()
+            );
+        } else {
+            panic!("did not return Err as expected")
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lazy_does_not_block_eject() -> Result<()> {
+        let i = Inject::default();
+
+        i.provide::<Left>(LeftProvider::default()).await?;
+        i.inject(Right {
+            left: i.lazy::<Left>(),
+        })
+        .await?;
+
+        // Resolve the Left service so that the container holds it
+        let _ = i.get::<Left>().await?;
+
+        // The Lazy handle inside Right doesn't keep the container alive
+        let right = i.eject::<Right>().await?;
+
+        assert!(right.left.get().await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lazy_mutual_references() -> Result<()> {
+        let i = Inject::default();
+
+        i.provide::<Left>(LeftProvider::default()).await?;
+        i.provide::<Right>(RightProvider::default()).await?;
+
+        let left = i.get::<Left>().await?;
+        let right = i.get::<Right>().await?;
+
+        assert!(Arc::ptr_eq(&left.right.get().await?, &right));
+        assert!(Arc::ptr_eq(&right.left.get().await?, &left));
+
+        Ok(())
+    }
+}
@@ -22,14 +22,20 @@ pub mod provider;
 /// Injector
 pub mod injector;
 
+/// Lazy Dependencies
+pub mod lazy;
+
 pub use container::Inject;
 pub use errors::{Error, Result};
 pub use injector::{Dependency, Pending};
 pub use key::{Id, Key};
+pub use lazy::Lazy;
 pub use provider::{to_provider_error, Provider};
 pub use tag::Tag;
 
 pub(crate) use injector::Injector;
 
+pub use nakago_derive::provider;
+
 #[doc(hidden)]
 pub use async_trait;
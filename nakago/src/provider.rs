@@ -81,9 +81,13 @@ pub(crate) mod test {
     use std::sync::Arc;
 
     use derive_new::new;
-    use nakago_derive::Provider;
+    use fake::Fake;
+    use nakago_derive::{Inject, Provider};
 
-    use crate::container::test::{HasId, OtherService, TestService};
+    use crate::{
+        container::test::{HasId, OtherService, TestService},
+        Lazy, Tag,
+    };
 
     use super::*;
 
@@ -135,4 +139,199 @@ pub(crate) mod test {
             ))))
         }
     }
+
+    // Derived Providers
+    // -----------------
+
+    pub const OTHER_TAG: Tag<OtherService> = Tag::new("provider::test::OtherService");
+
+    #[derive(Inject)]
+    #[inject(internal)]
+    pub struct InjectedService {
+        #[inject]
+        service: Arc<TestService>,
+
+        #[inject(tag = OTHER_TAG)]
+        other: Arc<OtherService>,
+
+        #[inject(optional)]
+        missing: Option<Arc<String>>,
+
+        #[inject(lazy)]
+        lazy: Lazy<TestService>,
+
+        count: usize,
+    }
+
+    #[derive(Inject)]
+    #[inject(internal, provider = ProvideHasId, boxed = dyn HasId)]
+    pub struct InjectedHasId {
+        #[inject]
+        service: Arc<TestService>,
+    }
+
+    impl HasId for InjectedHasId {
+        fn get_id(&self) -> String {
+            self.service.get_id()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_derive_inject_success() -> errors::Result<()> {
+        let i = Inject::default();
+
+        let expected: String = fake::uuid::UUIDv4.fake();
+        let other: String = fake::uuid::UUIDv4.fake();
+
+        i.provide::<TestService>(TestServiceProvider::new(expected.clone()))
+            .await?;
+        i.provide_tag(&OTHER_TAG, OtherServiceProvider::new(other.clone()))
+            .await?;
+        i.provide::<InjectedService>(InjectedServiceProvider::default())
+            .await?;
+
+        let result = i.get::<InjectedService>().await?;
+
+        assert_eq!(expected, result.service.id);
+        assert_eq!(other, result.other.other_id);
+        assert!(result.missing.is_none());
+        assert_eq!(expected, result.lazy.get().await?.id);
+        assert_eq!(0, result.count);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_derive_inject_not_found() -> errors::Result<()> {
+        let i = Inject::default();
+
+        i.provide::<InjectedService>(InjectedServiceProvider::default())
+            .await?;
+
+        let result = i.get::<InjectedService>().await;
+
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_derive_inject_boxed_success() -> errors::Result<()> {
+        let i = Inject::default();
+
+        let expected: String = fake::uuid::UUIDv4.fake();
+
+        i.inject(TestService::new(expected.clone())).await?;
+        i.provide::<Box<dyn HasId>>(ProvideHasId::default()).await?;
+
+        let result = i.get::<Box<dyn HasId>>().await?;
+
+        assert_eq!(expected, result.get_id());
+
+        Ok(())
+    }
+
+    // Function Providers
+    // ------------------
+
+    #[nakago_derive::provider(internal)]
+    async fn provide_other(
+        service: Arc<TestService>,
+        #[inject(optional)] missing: Option<Arc<String>>,
+    ) -> anyhow::Result<OtherService> {
+        if missing.is_some() {
+            anyhow::bail!("unexpected String in the container")
+        }
+
+        Ok(OtherService::new(service.id.clone()))
+    }
+
+    #[nakago_derive::provider(internal)]
+    fn provide_has_id(
+        #[inject(tag = OTHER_TAG)] other: Arc<OtherService>,
+        _i: Inject,
+    ) -> Box<dyn HasId> {
+        Box::new(OtherService::new(other.other_id.clone()))
+    }
+
+    pub const SHADOWED_TAG: Tag<OtherService> = Tag::new("provider::test::Shadowed");
+
+    /// A parameter named `i` doesn't shadow the container for the arguments after it
+    #[nakago_derive::provider(internal)]
+    #[allow(clippy::needless_pass_by_value)]
+    fn provide_shadowed(
+        #[inject(tag = OTHER_TAG)] i: Arc<OtherService>,
+        service: Arc<TestService>,
+    ) -> OtherService {
+        OtherService::new(format!("{}-{}", i.other_id, service.id))
+    }
+
+    #[tokio::test]
+    async fn test_provider_fn_success() -> errors::Result<()> {
+        let i = Inject::default();
+
+        let expected: String = fake::uuid::UUIDv4.fake();
+
+        i.inject(TestService::new(expected.clone())).await?;
+        i.provide::<OtherService>(provide_other).await?;
+
+        let result = i.get::<OtherService>().await?;
+
+        assert_eq!(expected, result.other_id);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_provider_fn_error() -> errors::Result<()> {
+        let i = Inject::default();
+
+        i.inject(TestService::new(fake::uuid::UUIDv4.fake()))
+            .await?;
+        i.inject("unexpected".to_string()).await?;
+        i.provide::<OtherService>(provide_other).await?;
+
+        let result = i.get::<OtherService>().await;
+
+        if let Err(errors::Error::Provider(err)) = result {
+            assert!(matches!(*err, Error::Any(_)));
+        } else {
+            panic!("did not return a Provider Err as expected")
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_provider_fn_sync_success() -> errors::Result<()> {
+        let i = Inject::default();
+
+        let expected: String = fake::uuid::UUIDv4.fake();
+
+        i.inject_tag(&OTHER_TAG, OtherService::new(expected.clone()))
+            .await?;
+        i.provide::<Box<dyn HasId>>(provide_has_id).await?;
+
+        let result = i.get::<Box<dyn HasId>>().await?;
+
+        assert_eq!(expected, result.get_id());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_provider_fn_shadowed_success() -> errors::Result<()> {
+        let i = Inject::default();
+
+        i.inject(TestService::new("service".to_string())).await?;
+        i.inject_tag(&OTHER_TAG, OtherService::new("other".to_string()))
+            .await?;
+        i.provide_tag(&SHADOWED_TAG, provide_shadowed).await?;
+
+        let result = i.get_tag(&SHADOWED_TAG).await?;
+
+        assert_eq!("other-service", result.other_id);
+
+        Ok(())
+    }
 }
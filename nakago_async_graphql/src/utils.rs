@@ -1,6 +1,10 @@
-use async_graphql::{Error, ErrorExtensions, MaybeUndefined};
+use async_graphql::{
+    connection::{Connection, Edge},
+    Error, ErrorExtensions, MaybeUndefined, OutputType, SimpleObject,
+};
 use fake::{Dummy, Fake, Faker};
 use hyper::StatusCode;
+use nakago_axum::utils::{CursorArgs, CursorResponse, InvalidCursor};
 use rand::Rng;
 
 /// A convenience function to create a GraphQL error with predictable extension props
@@ -26,6 +30,21 @@ pub fn as_graphql_error(
     })
 }
 
+/// A convenience function to create a GraphQL error from a failed cursor-paginated request,
+/// intended to be used with `.map_err()`. An `InvalidCursor` anywhere in the error chain is a
+/// client error reported as BAD_REQUEST, and anything else is an INTERNAL_SERVER_ERROR.
+pub fn as_cursor_error(message: &'static str) -> Box<dyn Fn(anyhow::Error) -> Error> {
+    Box::new(move |err| {
+        let code = if err.chain().any(|cause| cause.is::<InvalidCursor>()) {
+            StatusCode::BAD_REQUEST
+        } else {
+            StatusCode::INTERNAL_SERVER_ERROR
+        };
+
+        as_graphql_error(message, code)(err)
+    })
+}
+
 /// Randomly generate the `MaybeUndefined` type from the async-graphql library
 pub fn dummy_maybe_undef<T, R: Rng + ?Sized>(config: &Faker, rng: &mut R) -> MaybeUndefined<T>
 where
@@ -37,3 +56,253 @@ where
         _ => MaybeUndefined::Value(T::dummy_with_rng(config, rng)),
     }
 }
+
+/// The additional fields on a cursor-paginated `Connection`
+#[derive(Clone, Debug, Eq, PartialEq, SimpleObject)]
+pub struct TotalCount {
+    /// The total number of records available, if it was requested
+    pub total_count: Option<u64>,
+}
+
+/// Build `CursorArgs` from the standard Relay connection arguments, rejecting negative counts and
+/// `first` together with `last`
+pub fn cursor_args(
+    after: Option<String>,
+    before: Option<String>,
+    first: Option<i32>,
+    last: Option<i32>,
+    total: bool,
+) -> Result<CursorArgs, Error> {
+    let count = |value: Option<i32>| {
+        value.map(u64::try_from).transpose().map_err(|_err| {
+            graphql_error(
+                "The first and last arguments can't be negative",
+                StatusCode::BAD_REQUEST,
+            )
+        })
+    };
+
+    if first.is_some() && last.is_some() {
+        return Err(graphql_error(
+            "The first and last arguments can't be used together",
+            StatusCode::BAD_REQUEST,
+        ));
+    }
+
+    Ok(CursorArgs {
+        first: count(first)?,
+        after,
+        last: count(last)?,
+        before,
+        total,
+    })
+}
+
+/// Convert a `CursorResponse` into an async-graphql `Connection`, with the total as `totalCount`
+pub fn into_connection<Node: OutputType>(
+    response: CursorResponse<Node>,
+) -> Connection<String, Node, TotalCount> {
+    let mut connection = Connection::with_additional_fields(
+        response.page_info.has_previous_page,
+        response.page_info.has_next_page,
+        TotalCount {
+            total_count: response.total,
+        },
+    );
+
+    connection.edges.extend(
+        response
+            .edges
+            .into_iter()
+            .map(|edge| Edge::new(edge.cursor, edge.node)),
+    );
+
+    connection
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use async_graphql::Value;
+    use nakago_axum::utils::Edge as CursorEdge;
+    use nakago_sea_orm::{Filter, Ordering};
+    use pretty_assertions::assert_eq;
+    use sea_orm::{DbBackend, EntityTrait, QueryTrait};
+
+    use self::shows::*;
+    use super::*;
+
+    mod shows {
+        use nakago_derive::Filter;
+        use sea_orm::entity::prelude::*;
+
+        #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Filter)]
+        #[sea_orm(table_name = "shows")]
+        #[filter(name = "Show")]
+        pub struct Model {
+            #[sea_orm(primary_key, auto_increment = false)]
+            pub id: String,
+            pub title: String,
+            pub summary: Option<String>,
+            pub published: bool,
+            pub episodes: i32,
+            #[filter(skip)]
+            pub data: Json,
+        }
+
+        #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+        pub enum Relation {}
+
+        impl ActiveModelBehavior for ActiveModel {}
+    }
+
+    fn sql(condition: ShowCondition) -> String {
+        condition
+            .apply(Entity::find())
+            .build(DbBackend::Postgres)
+            .to_string()
+    }
+
+    #[test]
+    fn test_derive_filter() {
+        let condition = ShowCondition {
+            title: Some(ShowTitleFilter {
+                contains: Some("Show".to_string()),
+                ..Default::default()
+            }),
+            summary: Some(ShowSummaryFilter {
+                is_null: Some(true),
+                ..Default::default()
+            }),
+            episodes: Some(ShowEpisodesFilter {
+                gte: Some(2),
+                ..Default::default()
+            }),
+            or: Some(vec![
+                ShowCondition {
+                    published: Some(ShowPublishedFilter {
+                        eq: Some(true),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                ShowCondition {
+                    id: Some(ShowIdFilter {
+                        r#in: Some(vec!["1".to_string(), "2".to_string()]),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+            ]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            r#"SELECT "shows"."id", "shows"."title", "shows"."summary", "shows"."published", "shows"."episodes", "shows"."data" FROM "shows" WHERE "shows"."title" LIKE '%Show%' ESCAPE E'\\' AND "shows"."summary" IS NULL AND "shows"."episodes" >= 2 AND ("shows"."published" = TRUE OR "shows"."id" IN ('1', '2'))"#,
+            sql(condition)
+        );
+
+        // LIKE wildcards in contains match literally
+        let condition = ShowCondition {
+            title: Some(ShowTitleFilter {
+                contains: Some("50%_off".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            r#"SELECT "shows"."id", "shows"."title", "shows"."summary", "shows"."published", "shows"."episodes", "shows"."data" FROM "shows" WHERE "shows"."title" LIKE E'%50\\%\\_off%' ESCAPE E'\\'"#,
+            sql(condition)
+        );
+
+        // An empty condition doesn't filter anything
+        assert_eq!(
+            r#"SELECT "shows"."id", "shows"."title", "shows"."summary", "shows"."published", "shows"."episodes", "shows"."data" FROM "shows""#,
+            sql(ShowCondition::default())
+        );
+
+        let ordering: Ordering<Column> = ShowOrderBy::TitleDesc.into();
+        assert!(matches!(ordering, Ordering::Desc(Column::Title)));
+    }
+
+    fn code(err: &Error) -> Option<Value> {
+        err.extensions
+            .as_ref()
+            .and_then(|extensions| extensions.get("code").cloned())
+    }
+
+    #[test]
+    fn test_cursor_args() -> Result<(), Error> {
+        let args = cursor_args(Some("a".to_string()), None, Some(10), None, true)?;
+        assert_eq!(
+            CursorArgs {
+                first: Some(10),
+                after: Some("a".to_string()),
+                last: None,
+                before: None,
+                total: true,
+            },
+            args
+        );
+
+        let args = cursor_args(None, Some("b".to_string()), None, Some(5), false)?;
+        assert_eq!(Some(5), args.last);
+        assert_eq!(Some("b".to_string()), args.before);
+
+        for result in [
+            cursor_args(None, None, Some(-1), None, false),
+            cursor_args(None, None, None, Some(-1), false),
+            cursor_args(None, None, Some(1), Some(1), false),
+        ] {
+            let err = result.expect_err("the arguments should be rejected");
+            assert_eq!(Some(Value::from(400_u16)), code(&err));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_as_cursor_error() {
+        let err = as_cursor_error("Error while fetching Shows")(
+            anyhow::Error::from(InvalidCursor("not a cursor".to_string())).context("paginate"),
+        );
+        assert_eq!(Some(Value::from(400_u16)), code(&err));
+        assert_eq!("Error while fetching Shows", err.message);
+
+        let err = as_cursor_error("Error while fetching Shows")(anyhow!("connection refused"));
+        assert_eq!(Some(Value::from(500_u16)), code(&err));
+    }
+
+    #[test]
+    fn test_into_connection() {
+        let response = CursorResponse::new(
+            vec![
+                CursorEdge {
+                    cursor: "a".to_string(),
+                    node: 1,
+                },
+                CursorEdge {
+                    cursor: "b".to_string(),
+                    node: 2,
+                },
+            ],
+            true,
+            false,
+            Some(5),
+        );
+
+        let connection = into_connection(response);
+
+        assert!(connection.has_previous_page);
+        assert!(!connection.has_next_page);
+        assert_eq!(Some(5), connection.additional_fields.total_count);
+        assert_eq!(
+            vec![("a".to_string(), 1), ("b".to_string(), 2)],
+            connection
+                .edges
+                .into_iter()
+                .map(|edge| (edge.cursor, edge.node))
+                .collect::<Vec<_>>()
+        );
+    }
+}
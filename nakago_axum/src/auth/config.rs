@@ -1,10 +1,14 @@
 use figment::{providers::Env, Figment};
+use nakago_derive::Validate;
+use nakago_figment::Secret;
 use serde::{Deserialize, Serialize};
 
 /// Auth config
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Config {
-    /// OAuth2 url
+    /// OAuth2 url, which must include a host
+    #[validate(url)]
     pub url: String,
 
     /// OAuth2 audience
@@ -16,12 +20,13 @@ pub struct Config {
 
 /// Auth client config
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Client {
     /// OAuth2 client id
     pub id: Option<String>,
 
-    /// OAuth2 client secret
-    pub secret: Option<String>,
+    /// OAuth2 client secret, which is redacted when printed
+    pub secret: Option<Secret<String>>,
 }
 
 impl Default for Config {
@@ -5,9 +5,9 @@ use biscuit::{
     jwk::{AlgorithmParameters, JWK},
     jws::Secret,
 };
-use nakago::{self, provider, Inject, Provider, Tag};
+use nakago::{self, provider, to_provider_error, Inject, Provider, Tag};
 use nakago_derive::Provider;
-use nakago_figment::FromRef;
+use nakago_figment::{named, FromRef, Named};
 use serde::Deserialize;
 use thiserror::Error;
 
@@ -112,3 +112,139 @@ where
         Ok(Arc::new(key_set))
     }
 }
+
+/// Provide the Json Web Key Set for a named instance in a map-shaped auth section, such as
+/// `[auth.partner]`
+pub struct ProvideNamed<C: nakago_figment::Config, T = Empty> {
+    name: String,
+    config_tag: Option<&'static Tag<C>>,
+    _phantom: PhantomData<T>,
+}
+
+impl<C: nakago_figment::Config, T: Send + Sync + Any> ProvideNamed<C, T> {
+    /// Create a new instance of ProvideNamed for the given instance name
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            config_tag: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Set the config Tag for this instance
+    pub fn with_config_tag(self, config_tag: &'static Tag<C>) -> Self {
+        Self {
+            config_tag: Some(config_tag),
+            ..self
+        }
+    }
+}
+
+#[Provider]
+#[async_trait]
+impl<C: nakago_figment::Config, T: Send + Sync + Any + for<'de> Deserialize<'de>>
+    Provider<JWKSet<T>> for ProvideNamed<C, T>
+where
+    Named<Config>: FromRef<C>,
+{
+    async fn provide(self: Arc<Self>, i: Inject) -> provider::Result<Arc<JWKSet<T>>> {
+        let auth: Config = named::instance(&i, self.config_tag, &self.name).await?;
+
+        let key_set = Client::new(auth)
+            .get_key_set::<T>()
+            .await
+            .map_err(to_provider_error)?;
+
+        Ok(Arc::new(key_set))
+    }
+}
+
+/// Provide the Json Web Key Set for each of the given auth instances, under the `named::tag` for
+/// each instance name, such as `named::tag::<JWKSet<Empty>>("partner")`
+pub async fn provide_named<C, T>(i: &Inject, names: &[&str]) -> nakago::Result<()>
+where
+    C: nakago_figment::Config,
+    T: Send + Sync + Any + for<'de> Deserialize<'de>,
+    Named<Config>: FromRef<C>,
+{
+    for name in names {
+        i.provide_tag(
+            named::tag::<JWKSet<T>>(name),
+            ProvideNamed::<C, T>::new(name),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use axum::{extract::Path, routing::get, Json, Router};
+    use nakago_derive::Config;
+    use serde::Serialize;
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    #[derive(Clone, Debug, Default, Serialize, Deserialize, Config)]
+    pub struct Auths {
+        pub auth: Named<Config>,
+    }
+
+    // Serve a JWKS for each instance name, with a single key identified by the name
+    async fn serve() -> anyhow::Result<String> {
+        let router = Router::new().route(
+            "/{name}/.well-known/jwks.json",
+            get(|Path(name): Path<String>| async move {
+                Json::<Value>(json!({ "keys": [{ "kty": "oct", "kid": name, "k": "c2VjcmV0" }] }))
+            }),
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        Ok(format!("http://{addr}"))
+    }
+
+    #[tokio::test]
+    async fn test_provide_named_success() -> anyhow::Result<()> {
+        let i = Inject::default();
+        let url = serve().await?;
+
+        let instance = |name: &str| Config {
+            url: format!("{url}/{name}"),
+            ..Config::default()
+        };
+
+        let auth = Named::default()
+            .with("primary", instance("primary"))
+            .with("partner", instance("partner"));
+
+        i.inject(Auths { auth }).await?;
+
+        provide_named::<Auths, Empty>(&i, &["primary", "partner"]).await?;
+
+        let primary = i.get_tag(named::tag::<JWKSet<Empty>>("primary")).await?;
+        assert!(primary.find("primary").is_some());
+        assert!(primary.find("partner").is_none());
+
+        let partner = i.get_tag(named::tag::<JWKSet<Empty>>("partner")).await?;
+        assert!(partner.find("partner").is_some());
+
+        // An unknown instance name fails
+        i.provide_tag(
+            named::tag::<JWKSet<Empty>>("missing"),
+            ProvideNamed::<Auths>::new("missing"),
+        )
+        .await?;
+
+        let result = i.get_tag(named::tag::<JWKSet<Empty>>("missing")).await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+}
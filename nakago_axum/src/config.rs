@@ -17,6 +17,7 @@ pub fn default_loaders() -> Vec<Arc<dyn nakago_figment::Loader>> {
 
 /// Axum HTTP Config
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Config {
     /// The port to bind to
     pub port: u16,
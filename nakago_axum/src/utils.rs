@@ -1,4 +1,5 @@
-use serde::{Deserialize, Serialize};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 // Many Response
 // -------------
@@ -54,6 +55,124 @@ impl<Model> ManyResponse<Model> {
     }
 }
 
+// Cursor Response
+// ---------------
+
+/// Arguments for a cursor-paginated request, following the Relay connection spec. `first` and
+/// `after` page forwards, while `last` and `before` page backwards.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct CursorArgs {
+    /// The number of records to return from the start of the range
+    pub first: Option<u64>,
+    /// Return records after this cursor
+    pub after: Option<String>,
+    /// The number of records to return from the end of the range
+    pub last: Option<u64>,
+    /// Return records before this cursor
+    pub before: Option<String>,
+    /// Whether to count the total number of records, which requires an extra query
+    #[serde(default)]
+    pub total: bool,
+}
+
+/// Information about the page of records returned in a `CursorResponse`
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct PageInfo {
+    /// Whether there are more records after this page
+    pub has_next_page: bool,
+    /// Whether there are more records before this page
+    pub has_previous_page: bool,
+    /// The cursor of the first record in this page
+    pub start_cursor: Option<String>,
+    /// The cursor of the last record in this page
+    pub end_cursor: Option<String>,
+}
+
+/// A record in a `CursorResponse`, with the cursor that points to it
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct Edge<Model> {
+    /// The opaque cursor for the record
+    pub cursor: String,
+    /// The record
+    pub node: Model,
+}
+
+/// A cursor-paginated response for an entity, which stays stable while the underlying table
+/// changes and doesn't need to count or skip rows to find a page
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct CursorResponse<Model> {
+    /// The page of records being returned
+    pub edges: Vec<Edge<Model>>,
+    /// Information about the page
+    pub page_info: PageInfo,
+    /// The total number of records available, if it was requested
+    pub total: Option<u64>,
+}
+
+impl<Model> CursorResponse<Model> {
+    /// Create a new `CursorResponse` from a page of edges
+    pub fn new(
+        edges: Vec<Edge<Model>>,
+        has_previous_page: bool,
+        has_next_page: bool,
+        total: Option<u64>,
+    ) -> CursorResponse<Model> {
+        let page_info = PageInfo {
+            has_next_page,
+            has_previous_page,
+            start_cursor: edges.first().map(|edge| edge.cursor.clone()),
+            end_cursor: edges.last().map(|edge| edge.cursor.clone()),
+        };
+
+        Self {
+            edges,
+            page_info,
+            total,
+        }
+    }
+
+    /// Transform the records contained in the `CursorResponse`
+    pub fn map<B, F>(self, func: F) -> CursorResponse<B>
+    where
+        F: Fn(Model) -> B,
+    {
+        CursorResponse {
+            edges: self
+                .edges
+                .into_iter()
+                .map(|edge| Edge {
+                    cursor: edge.cursor,
+                    node: func(edge.node),
+                })
+                .collect(),
+            page_info: self.page_info,
+            total: self.total,
+        }
+    }
+}
+
+/// A cursor that couldn't be decoded
+#[derive(Debug, thiserror::Error)]
+#[error("invalid cursor: {0:?}")]
+pub struct InvalidCursor(pub String);
+
+/// Encode the sort key of a record as an opaque cursor
+pub fn encode_cursor<K: Serialize>(key: &K) -> String {
+    // Serializing plain keys such as strings, numbers and dates to JSON doesn't fail
+    let json = serde_json::to_vec(key).unwrap_or_default();
+
+    URL_SAFE_NO_PAD.encode(json)
+}
+
+/// Decode an opaque cursor back into the sort key of a record
+pub fn decode_cursor<K: DeserializeOwned>(cursor: &str) -> Result<K, InvalidCursor> {
+    let json = URL_SAFE_NO_PAD
+        .decode(cursor)
+        .map_err(|_err| InvalidCursor(cursor.to_string()))?;
+
+    serde_json::from_slice(&json).map_err(|_err| InvalidCursor(cursor.to_string()))
+}
+
 // Ordering
 // --------
 
@@ -63,3 +182,60 @@ pub enum Ordering<T> {
     Asc(T),
     Desc(T),
 }
+
+#[cfg(test)]
+pub(crate) mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() -> anyhow::Result<()> {
+        let key = ("2024-01-01T00:00:00".to_string(), 42_u64);
+
+        let cursor = encode_cursor(&key);
+        assert!(!cursor.contains('"'));
+
+        let decoded: (String, u64) = decode_cursor(&cursor)?;
+        assert_eq!(key, decoded);
+
+        assert!(decode_cursor::<(String, u64)>("not a cursor").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cursor_response_page_info() {
+        let edges = vec![
+            Edge {
+                cursor: "a".to_string(),
+                node: 1,
+            },
+            Edge {
+                cursor: "b".to_string(),
+                node: 2,
+            },
+        ];
+
+        let response = CursorResponse::new(edges, false, true, Some(5)).map(|node| node * 10);
+
+        assert_eq!(
+            PageInfo {
+                has_next_page: true,
+                has_previous_page: false,
+                start_cursor: Some("a".to_string()),
+                end_cursor: Some("b".to_string()),
+            },
+            response.page_info
+        );
+        assert_eq!(
+            vec![10, 20],
+            response
+                .edges
+                .iter()
+                .map(|edge| edge.node)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some(5), response.total);
+    }
+}
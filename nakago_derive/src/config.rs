@@ -0,0 +1,176 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    ItemStruct, LitStr, Token,
+};
+
+use crate::{
+    from_ref,
+    utils::{
+        combine_attribute, combine_unary_attribute, get_figment_crate_name, parse_attrs, Combine,
+    },
+};
+
+pub(crate) fn expand(item: ItemStruct) -> syn::Result<TokenStream> {
+    let figment = get_figment_crate_name();
+    let ident = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    let StructAttrs {
+        prefix,
+        separator,
+        validate,
+    } = parse_attrs("config", &item.attrs)?;
+
+    let mut overrides = Vec::new();
+    for field in &item.fields {
+        let FieldAttrs { env } = parse_attrs("config", &field.attrs)?;
+
+        if let Some((kw, env)) = env {
+            let Some(field_ident) = &field.ident else {
+                return Err(syn::Error::new_spanned(
+                    kw,
+                    "`env` can only be used on named fields",
+                ));
+            };
+
+            let name = field_ident.to_string();
+            overrides.push(quote! { (#env, #name) });
+        }
+    }
+
+    let prefix = prefix.map_or_else(
+        || quote! { ::std::option::Option::None },
+        |(_, prefix)| quote! { ::std::option::Option::Some(#prefix) },
+    );
+
+    let separator =
+        separator.map_or_else(|| quote! { "_" }, |(_, separator)| quote! { #separator });
+
+    // Run the derived Validate rules during `Init` if requested
+    let validate = validate.map(|_| {
+        quote! {
+            fn validate(&self, violations: &mut #figment::validate::Violations) {
+                #figment::validate::Validate::validate(self, violations)
+            }
+        }
+    });
+
+    let from_ref = from_ref::expand(item.clone())?;
+
+    Ok(quote! {
+        impl #impl_generics #figment::Config for #ident #ty_generics #where_clause {
+            fn env_options() -> #figment::env::Options {
+                #figment::env::Options {
+                    prefix: #prefix,
+                    separator: #separator,
+                    overrides: ::std::vec![#(#overrides),*],
+                }
+            }
+
+            #validate
+        }
+
+        #from_ref
+    })
+}
+
+mod kw {
+    syn::custom_keyword!(prefix);
+    syn::custom_keyword!(separator);
+    syn::custom_keyword!(env);
+    syn::custom_keyword!(validate);
+}
+
+#[derive(Default)]
+struct StructAttrs {
+    prefix: Option<(kw::prefix, LitStr)>,
+    separator: Option<(kw::separator, LitStr)>,
+    validate: Option<kw::validate>,
+}
+
+impl Parse for StructAttrs {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let mut prefix = None;
+        let mut separator = None;
+        let mut validate = None;
+
+        while !input.is_empty() {
+            let lh = input.lookahead1();
+            if lh.peek(kw::prefix) {
+                let kw = input.parse()?;
+                input.parse::<Token![=]>()?;
+                prefix = Some((kw, input.parse()?));
+            } else if lh.peek(kw::separator) {
+                let kw = input.parse()?;
+                input.parse::<Token![=]>()?;
+                let value: LitStr = input.parse()?;
+                if value.value().is_empty() {
+                    return Err(syn::Error::new_spanned(value, "`separator` can't be empty"));
+                }
+                separator = Some((kw, value));
+            } else if lh.peek(kw::validate) {
+                validate = Some(input.parse()?);
+            } else {
+                return Err(lh.error());
+            }
+
+            let _ = input.parse::<Token![,]>();
+        }
+
+        Ok(Self {
+            prefix,
+            separator,
+            validate,
+        })
+    }
+}
+
+impl Combine for StructAttrs {
+    fn combine(mut self, other: Self) -> syn::Result<Self> {
+        let Self {
+            prefix,
+            separator,
+            validate,
+        } = other;
+        combine_attribute(&mut self.prefix, prefix)?;
+        combine_attribute(&mut self.separator, separator)?;
+        combine_unary_attribute(&mut self.validate, validate)?;
+        Ok(self)
+    }
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    env: Option<(kw::env, LitStr)>,
+}
+
+impl Parse for FieldAttrs {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let mut env = None;
+
+        while !input.is_empty() {
+            let lh = input.lookahead1();
+            if lh.peek(kw::env) {
+                let kw = input.parse()?;
+                input.parse::<Token![=]>()?;
+                env = Some((kw, input.parse()?));
+            } else {
+                return Err(lh.error());
+            }
+
+            let _ = input.parse::<Token![,]>();
+        }
+
+        Ok(Self { env })
+    }
+}
+
+impl Combine for FieldAttrs {
+    fn combine(mut self, other: Self) -> syn::Result<Self> {
+        let Self { env } = other;
+        combine_attribute(&mut self.env, env)?;
+        Ok(self)
+    }
+}
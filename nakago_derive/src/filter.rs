@@ -0,0 +1,393 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    parse::{Parse, ParseStream},
+    GenericArgument, ItemStruct, LitStr, PathArguments, Token, Type,
+};
+
+use crate::utils::{
+    combine_attribute, combine_unary_attribute, get_package_crate_name, parse_attrs, Combine,
+};
+
+pub(crate) fn expand(item: ItemStruct) -> syn::Result<TokenStream> {
+    let graphql = get_package_crate_name("async-graphql");
+    let sea_orm = get_package_crate_name("sea-orm");
+    let nakago_sea_orm = get_package_crate_name("nakago-sea-orm");
+
+    let vis = &item.vis;
+
+    let StructAttrs { name } = parse_attrs("filter", &item.attrs)?;
+    let name = name.map_or_else(|| item.ident.to_string(), |(_, name)| name.value());
+
+    let condition = format_ident!("{name}Condition");
+    let order_by = format_ident!("{name}OrderBy");
+
+    let mut filters = Vec::new();
+    let mut condition_fields = Vec::new();
+    let mut condition_checks = Vec::new();
+    let mut variants = Vec::new();
+    let mut orderings = Vec::new();
+
+    for field in &item.fields {
+        let FieldAttrs { skip } = parse_attrs("filter", &field.attrs)?;
+        if skip.is_some() {
+            continue;
+        }
+
+        let Some(member) = &field.ident else {
+            return Err(syn::Error::new_spanned(
+                field,
+                "`#[derive(Filter)]` can only be used on structs with named fields",
+            ));
+        };
+
+        let field_name = member.to_string();
+        let field_name = field_name.trim_start_matches("r#");
+        let camel = to_upper_camel(field_name);
+
+        // SeaORM names each Column variant after its field in UpperCamelCase
+        let column = Ident::new(&camel, Span::call_site());
+        let filter = format_ident!("{name}{camel}Filter");
+
+        let (ty, nullable) = match option_inner(&field.ty) {
+            Some(inner) => (inner, true),
+            None => (&field.ty, false),
+        };
+
+        let kind = type_name(ty);
+        let is_string = kind.as_deref() == Some("String");
+        let is_bool = kind.as_deref() == Some("bool");
+
+        let mut ops = vec![
+            (quote! { eq }, quote! { #ty }, "Equal to the value"),
+            (quote! { ne }, quote! { #ty }, "Not equal to the value"),
+            (
+                quote! { r#in },
+                quote! { ::std::vec::Vec<#ty> },
+                "Equal to any of the values",
+            ),
+            (
+                quote! { not_in },
+                quote! { ::std::vec::Vec<#ty> },
+                "Equal to none of the values",
+            ),
+        ];
+
+        if !is_bool {
+            ops.extend([
+                (quote! { gt }, quote! { #ty }, "Greater than the value"),
+                (
+                    quote! { gte },
+                    quote! { #ty },
+                    "Greater than or equal to the value",
+                ),
+                (quote! { lt }, quote! { #ty }, "Less than the value"),
+                (
+                    quote! { lte },
+                    quote! { #ty },
+                    "Less than or equal to the value",
+                ),
+            ]);
+        }
+
+        let mut checks = ops
+            .iter()
+            .map(|(op, _, _)| {
+                let method = match op.to_string().as_str() {
+                    "r#in" => quote! { is_in },
+                    "not_in" => quote! { is_not_in },
+                    _ => op.clone(),
+                };
+
+                quote! {
+                    if let ::std::option::Option::Some(value) = filter.#op {
+                        condition = condition.add(
+                            #sea_orm::ColumnTrait::#method(&Column::#column, value),
+                        );
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if is_string {
+            ops.push((
+                quote! { contains },
+                quote! { ::std::string::String },
+                "Contains the value",
+            ));
+            checks.push(quote! {
+                if let ::std::option::Option::Some(value) = filter.contains {
+                    // Escape the LIKE wildcards so that they match literally
+                    let pattern = ::std::format!(
+                        "%{}%",
+                        value
+                            .replace('\\', "\\\\")
+                            .replace('%', "\\%")
+                            .replace('_', "\\_"),
+                    );
+
+                    condition = condition.add(
+                        #sea_orm::sea_query::Expr::col(
+                            #sea_orm::ColumnTrait::as_column_ref(&Column::#column),
+                        )
+                        .like(#sea_orm::sea_query::LikeExpr::new(pattern).escape('\\')),
+                    );
+                }
+            });
+        }
+
+        if nullable {
+            ops.push((
+                quote! { is_null },
+                quote! { bool },
+                "Whether the value is null",
+            ));
+            checks.push(quote! {
+                if let ::std::option::Option::Some(is_null) = filter.is_null {
+                    condition = condition.add(if is_null {
+                        #sea_orm::ColumnTrait::is_null(&Column::#column)
+                    } else {
+                        #sea_orm::ColumnTrait::is_not_null(&Column::#column)
+                    });
+                }
+            });
+        }
+
+        let filter_fields = ops.iter().map(|(op, ty, doc)| {
+            quote! {
+                #[doc = #doc]
+                pub #op: ::std::option::Option<#ty>,
+            }
+        });
+
+        let filter_doc = format!("Conditions on the {name} `{field_name}` field");
+        filters.push(quote! {
+            #[doc = #filter_doc]
+            #[derive(::std::clone::Clone, ::std::fmt::Debug, ::std::default::Default, #graphql::InputObject)]
+            #vis struct #filter {
+                #(#filter_fields)*
+            }
+        });
+
+        let field_doc = format!("Conditions on the `{field_name}` field");
+        condition_fields.push(quote! {
+            #[doc = #field_doc]
+            pub #member: ::std::option::Option<#filter>,
+        });
+
+        condition_checks.push(quote! {
+            if let ::std::option::Option::Some(filter) = self.#member {
+                #(#checks)*
+            }
+        });
+
+        let asc = format_ident!("{camel}Asc");
+        let desc = format_ident!("{camel}Desc");
+        let asc_doc = format!("Order ascending by `{field_name}`");
+        let desc_doc = format!("Order descending by `{field_name}`");
+
+        variants.push(quote! {
+            #[doc = #asc_doc]
+            #asc,
+            #[doc = #desc_doc]
+            #desc,
+        });
+
+        orderings.push(quote! {
+            #order_by::#asc => #nakago_sea_orm::Ordering::Asc(Column::#column),
+            #order_by::#desc => #nakago_sea_orm::Ordering::Desc(Column::#column),
+        });
+    }
+
+    let condition_doc = format!("Conditions to filter {name} listings by");
+    let order_by_doc = format!("The available orderings for {name} listings");
+
+    Ok(quote! {
+        #(#filters)*
+
+        #[doc = #condition_doc]
+        #[derive(::std::clone::Clone, ::std::fmt::Debug, ::std::default::Default, #graphql::InputObject)]
+        #vis struct #condition {
+            #(#condition_fields)*
+
+            /// Match all of the given conditions
+            pub and: ::std::option::Option<::std::vec::Vec<#condition>>,
+
+            /// Match any of the given conditions
+            pub or: ::std::option::Option<::std::vec::Vec<#condition>>,
+        }
+
+        impl #condition {
+            /// Translate the conditions into a SeaORM Condition
+            pub fn condition(self) -> #sea_orm::Condition {
+                let mut condition = #sea_orm::Condition::all();
+
+                #(#condition_checks)*
+
+                if let ::std::option::Option::Some(all) = self.and {
+                    for nested in all {
+                        condition = condition.add(nested.condition());
+                    }
+                }
+
+                if let ::std::option::Option::Some(any) = self.or {
+                    if !any.is_empty() {
+                        condition = condition.add(
+                            any.into_iter()
+                                .fold(#sea_orm::Condition::any(), |any, nested| {
+                                    any.add(nested.condition())
+                                }),
+                        );
+                    }
+                }
+
+                condition
+            }
+        }
+
+        impl #nakago_sea_orm::Filter<Entity> for #condition {
+            fn apply(self, query: #sea_orm::Select<Entity>) -> #sea_orm::Select<Entity> {
+                let condition = self.condition();
+
+                if condition.is_empty() {
+                    return query;
+                }
+
+                #sea_orm::QueryFilter::filter(query, condition)
+            }
+        }
+
+        #[doc = #order_by_doc]
+        #[derive(
+            ::std::marker::Copy,
+            ::std::clone::Clone,
+            ::std::fmt::Debug,
+            ::std::cmp::Eq,
+            ::std::cmp::PartialEq,
+            #graphql::Enum,
+        )]
+        #vis enum #order_by {
+            #(#variants)*
+        }
+
+        impl ::std::convert::From<#order_by> for #nakago_sea_orm::Ordering<Column> {
+            fn from(order_by: #order_by) -> Self {
+                match order_by {
+                    #(#orderings)*
+                }
+            }
+        }
+    })
+}
+
+// The inner type of an `Option<T>`
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+
+    match args.args.first()? {
+        GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
+}
+
+// The name of the last segment of a type path, such as `String` for `std::string::String`
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) => path.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    }
+}
+
+fn to_upper_camel(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_uppercase().chain(chars).collect()
+            })
+        })
+        .collect()
+}
+
+mod kw {
+    syn::custom_keyword!(name);
+    syn::custom_keyword!(skip);
+}
+
+#[derive(Default)]
+struct StructAttrs {
+    name: Option<(kw::name, LitStr)>,
+}
+
+impl Parse for StructAttrs {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let mut name = None;
+
+        while !input.is_empty() {
+            let lh = input.lookahead1();
+            if lh.peek(kw::name) {
+                let kw = input.parse()?;
+                input.parse::<Token![=]>()?;
+                name = Some((kw, input.parse()?));
+            } else {
+                return Err(lh.error());
+            }
+
+            let _ = input.parse::<Token![,]>();
+        }
+
+        Ok(Self { name })
+    }
+}
+
+impl Combine for StructAttrs {
+    fn combine(mut self, other: Self) -> syn::Result<Self> {
+        let Self { name } = other;
+        combine_attribute(&mut self.name, name)?;
+        Ok(self)
+    }
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    skip: Option<kw::skip>,
+}
+
+impl Parse for FieldAttrs {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let mut skip = None;
+
+        while !input.is_empty() {
+            let lh = input.lookahead1();
+            if lh.peek(kw::skip) {
+                skip = Some(input.parse()?);
+            } else {
+                return Err(lh.error());
+            }
+
+            let _ = input.parse::<Token![,]>();
+        }
+
+        Ok(Self { skip })
+    }
+}
+
+impl Combine for FieldAttrs {
+    fn combine(mut self, other: Self) -> syn::Result<Self> {
+        let Self { skip } = other;
+        combine_unary_attribute(&mut self.skip, skip)?;
+        Ok(self)
+    }
+}
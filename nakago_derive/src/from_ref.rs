@@ -1,85 +1,152 @@
-use proc_macro2::{Ident, TokenStream};
+use proc_macro2::TokenStream;
 use quote::quote_spanned;
 use syn::{
+    parenthesized,
     parse::{Parse, ParseStream},
+    punctuated::Punctuated,
     spanned::Spanned,
-    Field, ItemStruct, Token, Type,
+    token, Field, GenericParam, Generics, ItemStruct, Member, Token, Type,
 };
 
-use crate::utils::{combine_unary_attribute, parse_attrs, Combine};
+use crate::utils::{
+    combine_attribute, combine_unary_attribute, get_figment_crate_name, parse_attrs, Combine,
+};
 
 pub(crate) fn expand(item: ItemStruct) -> syn::Result<TokenStream> {
-    if !item.generics.params.is_empty() {
-        return Err(syn::Error::new_spanned(
-            item.generics,
-            "`#[derive(FromRef)]` doesn't support generics",
-        ));
-    }
-
     let tokens = item
         .fields
         .iter()
         .enumerate()
-        .map(|(idx, field)| expand_field(&item.ident, idx, field))
+        .map(|(idx, field)| expand_field(&item, idx, field))
         .collect();
 
     Ok(tokens)
 }
 
-fn expand_field(state: &Ident, idx: usize, field: &Field) -> TokenStream {
-    let FieldAttrs { skip } = match parse_attrs("from_ref", &field.attrs) {
+fn expand_field(item: &ItemStruct, idx: usize, field: &Field) -> TokenStream {
+    let FieldAttrs { skip, flatten } = match parse_attrs("from_ref", &field.attrs) {
         Ok(attrs) => attrs,
         Err(err) => return err.into_compile_error(),
     };
 
-    if skip.is_some() {
+    // A field that is just a generic param can't be the target of an impl, because of the orphan
+    // rules, so it is skipped automatically
+    if skip.is_some() || is_type_param(&item.generics, &field.ty) {
         return TokenStream::default();
     }
 
+    let figment = get_figment_crate_name();
+    let state = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+    let predicates = where_clause.map(|clause| &clause.predicates);
+
     let field_ty = &field.ty;
     let span = field.ty.span();
 
-    let body = if let Some(field_ident) = &field.ident {
-        if matches!(field_ty, Type::Reference(_)) {
-            quote_spanned! {span=> state.#field_ident }
-        } else {
-            quote_spanned! {span=> state.#field_ident.clone() }
-        }
-    } else {
-        let idx = syn::Index {
+    let member = match &field.ident {
+        Some(field_ident) => Member::Named(field_ident.clone()),
+        None => Member::Unnamed(syn::Index {
             index: idx as _,
             span: field.span(),
-        };
-        quote_spanned! {span=> state.#idx.clone() }
+        }),
     };
 
-    quote_spanned! {span=>
+    let body = if matches!(field_ty, Type::Reference(_)) {
+        quote_spanned! {span=> state.#member }
+    } else {
+        quote_spanned! {span=> state.#member.clone() }
+    };
+
+    let mut tokens = quote_spanned! {span=>
         #[allow(clippy::clone_on_copy)]
-        impl ::nakago_figment::FromRef<#state> for #field_ty {
-            fn from_ref(state: &#state) -> Self {
+        impl #impl_generics #figment::FromRef<#state #ty_generics> for #field_ty
+        where
+            #field_ty: ::std::clone::Clone,
+            #predicates
+        {
+            fn from_ref(state: &#state #ty_generics) -> Self {
                 #body
             }
         }
+    };
+
+    // Pass through the FromRef impls of the nested config for each of the given types
+    if let Some((_, nested)) = flatten {
+        for ty in nested {
+            let span = ty.span();
+
+            tokens.extend(quote_spanned! {span=>
+                impl #impl_generics #figment::FromRef<#state #ty_generics> for #ty
+                where
+                    #ty: #figment::FromRef<#field_ty>,
+                    #predicates
+                {
+                    fn from_ref(state: &#state #ty_generics) -> Self {
+                        <#ty as #figment::FromRef<#field_ty>>::from_ref(&state.#member)
+                    }
+                }
+            });
+        }
+    }
+
+    tokens
+}
+
+// Check whether the given type is exactly one of the struct's generic type params
+fn is_type_param(generics: &Generics, ty: &Type) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+
+    if path.qself.is_some() {
+        return false;
     }
+
+    let Some(ident) = path.path.get_ident() else {
+        return false;
+    };
+
+    generics.params.iter().any(|param| match param {
+        GenericParam::Type(param) => &param.ident == ident,
+        _ => false,
+    })
 }
 
 mod kw {
     syn::custom_keyword!(skip);
+    syn::custom_keyword!(flatten);
 }
 
 #[derive(Default)]
 pub(super) struct FieldAttrs {
     pub(super) skip: Option<kw::skip>,
+    pub(super) flatten: Option<(kw::flatten, Punctuated<Type, Token![,]>)>,
 }
 
 impl Parse for FieldAttrs {
     fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
         let mut skip = None;
+        let mut flatten = None;
 
         while !input.is_empty() {
             let lh = input.lookahead1();
             if lh.peek(kw::skip) {
                 skip = Some(input.parse()?);
+            } else if lh.peek(kw::flatten) {
+                let kw: kw::flatten = input.parse()?;
+
+                // A derive can't see the FromRef impls of another type, so they must be listed
+                if !input.peek(token::Paren) {
+                    return Err(syn::Error::new(
+                        kw.span,
+                        "`flatten` needs the types to pass through from the nested config, like \
+                         `#[from_ref(flatten(A, B))]`",
+                    ));
+                }
+
+                let content;
+                parenthesized!(content in input);
+                flatten = Some((kw, content.parse_terminated(Type::parse, Token![,])?));
             } else {
                 return Err(lh.error());
             }
@@ -87,14 +154,15 @@ impl Parse for FieldAttrs {
             let _ = input.parse::<Token![,]>();
         }
 
-        Ok(Self { skip })
+        Ok(Self { skip, flatten })
     }
 }
 
 impl Combine for FieldAttrs {
     fn combine(mut self, other: Self) -> syn::Result<Self> {
-        let Self { skip } = other;
+        let Self { skip, flatten } = other;
         combine_unary_attribute(&mut self.skip, skip)?;
+        combine_attribute(&mut self.flatten, flatten)?;
         Ok(self)
     }
 }
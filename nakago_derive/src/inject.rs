@@ -0,0 +1,437 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    parse::{Parse, ParseStream},
+    spanned::Spanned,
+    Field, Fields, GenericArgument, ItemStruct, Meta, Path, PathArguments, Token, Type,
+};
+
+use crate::utils::{
+    combine_attribute, combine_unary_attribute, get_crate_name, get_figment_crate_name,
+    parse_attrs, Combine,
+};
+
+pub(crate) fn expand(item: ItemStruct) -> syn::Result<TokenStream> {
+    if !item.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            item.generics,
+            "`#[derive(Inject)]` doesn't support generics",
+        ));
+    }
+
+    let StructAttrs {
+        provider,
+        boxed,
+        internal,
+    } = parse_attrs("inject", &item.attrs)?;
+
+    let ident = &item.ident;
+    let vis = &item.vis;
+    let crate_name = get_crate_name(internal.is_some());
+
+    let provider = match provider {
+        Some((_, provider)) => provider,
+        None => format_ident!("{}Provider", ident),
+    };
+
+    let mut bindings = Vec::new();
+    let mut config_types = Vec::new();
+
+    for (idx, field) in item.fields.iter().enumerate() {
+        let binding = format_ident!("field_{}", idx);
+        let value = expand_field(field)?;
+
+        if let Value::Config(ty) = &value {
+            config_types.push(ty.clone());
+        }
+
+        bindings.push((binding, value));
+    }
+
+    let lets = bindings.iter().map(|(binding, value)| {
+        let expr = value.to_tokens();
+        quote! { let #binding = #expr; }
+    });
+
+    let names = bindings.iter().map(|(binding, _)| binding);
+    let construct = match &item.fields {
+        Fields::Named(fields) => {
+            let idents = fields.named.iter().map(|field| &field.ident);
+            quote! { #ident { #(#idents: #names),* } }
+        }
+        Fields::Unnamed(_) => quote! { #ident(#(#names),*) },
+        Fields::Unit => quote! { #ident },
+    };
+
+    let (output, value) = match boxed {
+        Some((_, ty)) => (
+            quote! { ::std::boxed::Box<#ty> },
+            quote! { ::std::boxed::Box::new(#construct) },
+        ),
+        None => (quote! { #ident }, construct),
+    };
+
+    let doc = format!("Provide the `{ident}` with its injected fields");
+
+    let expanded = if config_types.is_empty() {
+        quote! {
+            #[doc = #doc]
+            #[derive(Default)]
+            #vis struct #provider {}
+
+            #[allow(clippy::all, clippy::pedantic)]
+            #[#crate_name::async_trait::async_trait]
+            impl #crate_name::Provider<#output> for #provider {
+                async fn provide(
+                    self: ::std::sync::Arc<Self>,
+                    i: #crate_name::Inject,
+                ) -> #crate_name::provider::Result<::std::sync::Arc<#output>> {
+                    #(#lets)*
+
+                    Ok(::std::sync::Arc::new(#value))
+                }
+            }
+
+            #[allow(clippy::all, clippy::pedantic)]
+            #[#crate_name::async_trait::async_trait]
+            impl #crate_name::Provider<#crate_name::Dependency> for #provider {
+                async fn provide(
+                    self: ::std::sync::Arc<Self>,
+                    i: #crate_name::Inject,
+                ) -> #crate_name::provider::Result<::std::sync::Arc<#crate_name::Dependency>> {
+                    let provider = self as ::std::sync::Arc<dyn #crate_name::Provider<#output>>;
+
+                    Ok(provider.provide(i).await?)
+                }
+            }
+        }
+    } else {
+        let figment = get_figment_crate_name();
+
+        quote! {
+            #[doc = #doc]
+            #vis struct #provider<C: #figment::Config> {
+                config_tag: ::std::option::Option<&'static #crate_name::Tag<C>>,
+            }
+
+            impl<C: #figment::Config> ::std::default::Default for #provider<C> {
+                fn default() -> Self {
+                    Self { config_tag: None }
+                }
+            }
+
+            impl<C: #figment::Config> #provider<C> {
+                /// Create a new instance of the Provider
+                pub fn new() -> Self {
+                    Self::default()
+                }
+
+                /// Set the config Tag for this instance
+                pub fn with_config_tag(self, config_tag: &'static #crate_name::Tag<C>) -> Self {
+                    Self {
+                        config_tag: Some(config_tag),
+                    }
+                }
+            }
+
+            #[allow(clippy::all, clippy::pedantic)]
+            #[#crate_name::async_trait::async_trait]
+            impl<C: #figment::Config> #crate_name::Provider<#output> for #provider<C>
+            where
+                #(#config_types: #figment::FromRef<C>,)*
+            {
+                async fn provide(
+                    self: ::std::sync::Arc<Self>,
+                    i: #crate_name::Inject,
+                ) -> #crate_name::provider::Result<::std::sync::Arc<#output>> {
+                    let config = if let Some(tag) = self.config_tag {
+                        i.get_tag(tag).await?
+                    } else {
+                        i.get::<C>().await?
+                    };
+
+                    #(#lets)*
+
+                    Ok(::std::sync::Arc::new(#value))
+                }
+            }
+
+            #[allow(clippy::all, clippy::pedantic)]
+            #[#crate_name::async_trait::async_trait]
+            impl<C: #figment::Config> #crate_name::Provider<#crate_name::Dependency> for #provider<C>
+            where
+                #(#config_types: #figment::FromRef<C>,)*
+            {
+                async fn provide(
+                    self: ::std::sync::Arc<Self>,
+                    i: #crate_name::Inject,
+                ) -> #crate_name::provider::Result<::std::sync::Arc<#crate_name::Dependency>> {
+                    let provider = self as ::std::sync::Arc<dyn #crate_name::Provider<#output>>;
+
+                    Ok(provider.provide(i).await?)
+                }
+            }
+        }
+    };
+
+    Ok(expanded)
+}
+
+// How an individual field will be resolved inside the generated Provider
+pub(crate) enum Value {
+    Get(Type),
+    GetOpt(Type),
+    GetTag(Path),
+    GetTagOpt(Path),
+    Lazy(Type),
+    LazyTag(Path),
+    Config(Type),
+    Default(Type),
+}
+
+impl Value {
+    pub(crate) fn to_tokens(&self) -> TokenStream {
+        match self {
+            Value::Get(ty) => quote_spanned! {ty.span()=> i.get::<#ty>().await? },
+            Value::GetOpt(ty) => quote_spanned! {ty.span()=> i.get_opt::<#ty>().await? },
+            Value::GetTag(tag) => quote_spanned! {tag.span()=> i.get_tag(&#tag).await? },
+            Value::GetTagOpt(tag) => quote_spanned! {tag.span()=> i.get_tag_opt(&#tag).await? },
+            Value::Lazy(ty) => quote_spanned! {ty.span()=> i.lazy::<#ty>() },
+            Value::LazyTag(tag) => quote_spanned! {tag.span()=> i.lazy_tag(&#tag) },
+            Value::Config(ty) => {
+                let figment = get_figment_crate_name();
+                quote_spanned! {ty.span()=> <#ty as #figment::FromRef<_>>::from_ref(&*config) }
+            }
+            Value::Default(ty) => {
+                quote_spanned! {ty.span()=> <#ty as ::std::default::Default>::default() }
+            }
+        }
+    }
+}
+
+fn expand_field(field: &Field) -> syn::Result<Value> {
+    match parse_field_attrs(&field.attrs)? {
+        Some(attrs) => expand_value(&field.ty, attrs),
+        None => Ok(Value::Default(field.ty.clone())),
+    }
+}
+
+// Determine how a value of the given type should be resolved from the container
+pub(crate) fn expand_value(ty: &Type, attrs: FieldAttrs) -> syn::Result<Value> {
+    let FieldAttrs {
+        tag,
+        optional,
+        lazy,
+        config,
+    } = attrs;
+
+    if let Some(config) = config {
+        if tag.is_some() || optional.is_some() || lazy.is_some() {
+            return Err(syn::Error::new_spanned(
+                config,
+                "`config` can't be combined with other `#[inject]` options",
+            ));
+        }
+
+        return Ok(Value::Config(ty.clone()));
+    }
+
+    if let Some(lazy) = lazy {
+        if optional.is_some() {
+            return Err(syn::Error::new_spanned(
+                lazy,
+                "`lazy` can't be combined with `optional`",
+            ));
+        }
+
+        return match tag {
+            Some((_, tag)) => Ok(Value::LazyTag(tag)),
+            None => inner_type(ty, "Lazy")
+                .map(|inner| Value::Lazy(inner.clone()))
+                .ok_or_else(|| {
+                    syn::Error::new_spanned(ty, "`#[inject(lazy)]` fields must be `Lazy<T>`")
+                }),
+        };
+    }
+
+    if optional.is_some() {
+        return match tag {
+            Some((_, tag)) => Ok(Value::GetTagOpt(tag)),
+            None => inner_type(ty, "Option")
+                .and_then(|option| inner_type(option, "Arc"))
+                .map(|inner| Value::GetOpt(inner.clone()))
+                .ok_or_else(|| {
+                    syn::Error::new_spanned(
+                        ty,
+                        "`#[inject(optional)]` fields must be `Option<Arc<T>>`",
+                    )
+                }),
+        };
+    }
+
+    match tag {
+        Some((_, tag)) => Ok(Value::GetTag(tag)),
+        None => inner_type(ty, "Arc")
+            .map(|inner| Value::Get(inner.clone()))
+            .ok_or_else(|| syn::Error::new_spanned(ty, "`#[inject]` fields must be `Arc<T>`")),
+    }
+}
+
+// Find the single type argument of a wrapper type like `Arc<T>`, matching on the last path segment
+pub(crate) fn inner_type<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+
+    let segment = path.path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+
+    match args.args.first() {
+        Some(GenericArgument::Type(inner)) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+// Field attributes may be a bare `#[inject]`, so they can't go through `parse_attrs` directly
+pub(crate) fn parse_field_attrs(attrs: &[syn::Attribute]) -> syn::Result<Option<FieldAttrs>> {
+    attrs
+        .iter()
+        .filter(|attr| attr.meta.path().is_ident("inject"))
+        .map(|attr| match &attr.meta {
+            Meta::Path(_) => Ok(FieldAttrs::default()),
+            _ => attr.parse_args::<FieldAttrs>(),
+        })
+        .try_fold(None, |out: Option<FieldAttrs>, next| match out {
+            Some(out) => Ok(Some(out.combine(next?)?)),
+            None => Ok(Some(next?)),
+        })
+}
+
+mod kw {
+    syn::custom_keyword!(provider);
+    syn::custom_keyword!(boxed);
+    syn::custom_keyword!(internal);
+    syn::custom_keyword!(tag);
+    syn::custom_keyword!(optional);
+    syn::custom_keyword!(lazy);
+    syn::custom_keyword!(config);
+}
+
+#[derive(Default)]
+pub(super) struct StructAttrs {
+    pub(super) provider: Option<(kw::provider, Ident)>,
+    pub(super) boxed: Option<(kw::boxed, Type)>,
+    pub(super) internal: Option<kw::internal>,
+}
+
+impl Parse for StructAttrs {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let mut provider = None;
+        let mut boxed = None;
+        let mut internal = None;
+
+        while !input.is_empty() {
+            let lh = input.lookahead1();
+            if lh.peek(kw::provider) {
+                let kw = input.parse()?;
+                input.parse::<Token![=]>()?;
+                provider = Some((kw, input.parse()?));
+            } else if lh.peek(kw::boxed) {
+                let kw = input.parse()?;
+                input.parse::<Token![=]>()?;
+                boxed = Some((kw, input.parse()?));
+            } else if lh.peek(kw::internal) {
+                internal = Some(input.parse()?);
+            } else {
+                return Err(lh.error());
+            }
+
+            let _ = input.parse::<Token![,]>();
+        }
+
+        Ok(Self {
+            provider,
+            boxed,
+            internal,
+        })
+    }
+}
+
+impl Combine for StructAttrs {
+    fn combine(mut self, other: Self) -> syn::Result<Self> {
+        let Self {
+            provider,
+            boxed,
+            internal,
+        } = other;
+        combine_attribute(&mut self.provider, provider)?;
+        combine_attribute(&mut self.boxed, boxed)?;
+        combine_unary_attribute(&mut self.internal, internal)?;
+        Ok(self)
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    pub(crate) tag: Option<(kw::tag, Path)>,
+    pub(crate) optional: Option<kw::optional>,
+    pub(crate) lazy: Option<kw::lazy>,
+    pub(crate) config: Option<kw::config>,
+}
+
+impl Parse for FieldAttrs {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let mut tag = None;
+        let mut optional = None;
+        let mut lazy = None;
+        let mut config = None;
+
+        while !input.is_empty() {
+            let lh = input.lookahead1();
+            if lh.peek(kw::tag) {
+                let kw = input.parse()?;
+                input.parse::<Token![=]>()?;
+                tag = Some((kw, input.parse()?));
+            } else if lh.peek(kw::optional) {
+                optional = Some(input.parse()?);
+            } else if lh.peek(kw::lazy) {
+                lazy = Some(input.parse()?);
+            } else if lh.peek(kw::config) {
+                config = Some(input.parse()?);
+            } else {
+                return Err(lh.error());
+            }
+
+            let _ = input.parse::<Token![,]>();
+        }
+
+        Ok(Self {
+            tag,
+            optional,
+            lazy,
+            config,
+        })
+    }
+}
+
+impl Combine for FieldAttrs {
+    fn combine(mut self, other: Self) -> syn::Result<Self> {
+        let Self {
+            tag,
+            optional,
+            lazy,
+            config,
+        } = other;
+        combine_attribute(&mut self.tag, tag)?;
+        combine_unary_attribute(&mut self.optional, optional)?;
+        combine_unary_attribute(&mut self.lazy, lazy)?;
+        combine_unary_attribute(&mut self.config, config)?;
+        Ok(self)
+    }
+}
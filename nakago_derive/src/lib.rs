@@ -1,14 +1,19 @@
 //! # Derive
 use darling::FromMeta;
 use proc_macro::TokenStream;
-use syn::{parse_macro_input, ItemImpl};
+use syn::{parse_macro_input, ItemFn, ItemImpl};
 
 use crate::utils::expand_with;
 
 mod args;
+mod config;
+mod filter;
 mod from_ref;
+mod inject;
 mod provider;
+mod provider_fn;
 mod utils;
+mod validate;
 
 macro_rules! parse_nested_meta {
     ($ty:ty, $args:expr) => {{
@@ -40,10 +45,93 @@ pub fn Provider(args: TokenStream, input: TokenStream) -> TokenStream {
     }
 }
 
+/// Turn a function into a Provider, resolving each argument from the container.
+///
+/// The function is replaced with a unit struct of the same name, so it can be passed directly to
+/// `i.provide(db)`. Arguments must be `Arc<T>` and are retrieved with `i.get::<T>()`, and they
+/// accept the same `#[inject(tag = TAG)]`, `#[inject(optional)]` and `#[inject(lazy)]` options as
+/// `#[derive(Inject)]`. An argument of type `Inject` receives the container itself. If the function
+/// returns a `Result`, the error is converted with `to_provider_error`. Doc comments move to the
+/// struct, and other attributes stay on the function.
+#[proc_macro_attribute]
+pub fn provider(args: TokenStream, input: TokenStream) -> TokenStream {
+    let object_args = parse_nested_meta!(args::Provider, args);
+    let item_fn = parse_macro_input!(input as ItemFn);
+    match provider_fn::generate(&object_args, item_fn) {
+        Ok(expanded) => expanded,
+        Err(err) => err.write_errors().into(),
+    }
+}
+
 /// Derive an implementation of [`FromRef`] for each field in a struct.
 ///
+/// Generic structs are supported, though fields whose type is just a generic param are skipped. Use
+/// `#[from_ref(skip)]` to skip a field, and `#[from_ref(flatten(A, B))]` to pass through the
+/// `FromRef` impls that a nested config provides for `A` and `B`. The derive can't discover which
+/// impls the nested config has, so a bare `#[from_ref(flatten)]` is rejected and the types must be
+/// listed.
+///
 /// [`FromRef`]: https://docs.rs/axum/0.7/axum/extract/trait.FromRef.html
 #[proc_macro_derive(FromRef, attributes(from_ref))]
 pub fn derive_from_ref(item: TokenStream) -> TokenStream {
     expand_with(item, from_ref::expand)
 }
+
+/// Derive the `Config` trait for an application config struct, along with the `FromRef` impls that
+/// `#[derive(FromRef)]` would generate for each field.
+///
+/// The generated `env_options()` drive the `nakago_figment::env::Loader`, which maps environment
+/// variables onto nested keys using the shape of the Config's default value. Use
+/// `#[config(prefix = "APP")]` to require a prefix on every variable, `#[config(separator = "__")]`
+/// to change the separator between nested keys, and `#[config(env = "DB")]` on a field to read its
+/// section from variables starting with `DB` rather than the uppercased field name. Add
+/// `#[config(validate)]` to run the struct's `Validate` impl when the Config is initialized.
+#[proc_macro_derive(Config, attributes(config, from_ref))]
+pub fn derive_config(item: TokenStream) -> TokenStream {
+    expand_with(item, config::expand)
+}
+
+/// Derive the `Validate` trait for a Config section.
+///
+/// Fields accept `#[validate(url)]`, `#[validate(non_empty)]`, `#[validate(range(min = 1, max =
+/// 10))]`, and `#[validate(nested)]` to validate a sub-section under its own dotted path. Use
+/// `#[validate(custom = check_fn)]` on the struct for cross-field rules, where `check_fn` takes
+/// `(&Self, &mut Violations)`.
+#[proc_macro_derive(Validate, attributes(validate))]
+pub fn derive_validate(item: TokenStream) -> TokenStream {
+    expand_with(item, validate::expand)
+}
+
+/// Derive a Provider for a struct, resolving each field marked with `#[inject]` from the container.
+///
+/// Fields marked `#[inject]` must be `Arc<T>` and are retrieved with `i.get::<T>()`. Use
+/// `#[inject(tag = TAG)]` to retrieve a Tagged Dependency, `#[inject(optional)]` for an
+/// `Option<Arc<T>>`, `#[inject(lazy)]` for a `Lazy<T>` handle, and `#[inject(config)]` to pull a
+/// section out of the application Config with `FromRef`. Fields without an `#[inject]` attribute
+/// are filled in with `Default::default()`.
+///
+/// The generated Provider is named `{Struct}Provider` unless `#[inject(provider = Name)]` is given,
+/// and it provides the struct itself unless `#[inject(boxed = dyn Trait)]` is given, in which case
+/// it provides `Box<dyn Trait>`. If any fields use `config`, the Provider is generic over the
+/// application Config type and accepts an optional config Tag.
+#[proc_macro_derive(Inject, attributes(inject))]
+pub fn derive_inject(item: TokenStream) -> TokenStream {
+    expand_with(item, inject::expand)
+}
+
+/// Derive GraphQL filter and order-by inputs for a SeaORM `Model`.
+///
+/// Generates a `{Name}Condition` input with a `{Name}{Field}Filter` for each column, offering
+/// `eq`, `ne`, `in` and `notIn`, plus `gt`, `gte`, `lt` and `lte` for non-boolean columns,
+/// `contains` for strings, where `%` and `_` match literally, and `isNull` for nullable columns.
+/// Conditions combine with `and` and `or`, and implement `nakago_sea_orm::Filter` for the `Entity`
+/// in the same module. A `{Name}OrderBy` enum gets `{FIELD}_ASC` and `{FIELD}_DESC` variants that
+/// convert into `nakago_sea_orm::Ordering<Column>`.
+///
+/// The name defaults to the struct name, so use `#[filter(name = "Show")]` on a `Model`. Use
+/// `#[filter(skip)]` on a field to leave it out, such as for a column type that isn't a GraphQL
+/// input.
+#[proc_macro_derive(Filter, attributes(filter))]
+pub fn derive_filter(item: TokenStream) -> TokenStream {
+    expand_with(item, filter::expand)
+}
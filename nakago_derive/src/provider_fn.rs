@@ -0,0 +1,176 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{FnArg, GenericArgument, ItemFn, PathArguments, ReturnType, Type};
+
+use crate::{
+    args,
+    inject::{expand_value, parse_field_attrs, Value},
+    utils::{get_crate_name, GeneratorResult},
+};
+
+pub fn generate(object_args: &args::Provider, item_fn: ItemFn) -> GeneratorResult<TokenStream> {
+    let crate_name = get_crate_name(object_args.internal);
+
+    let ItemFn {
+        attrs,
+        vis,
+        mut sig,
+        block,
+    } = item_fn;
+
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            sig.generics,
+            "`#[provider]` functions don't support generics",
+        )
+        .into());
+    }
+
+    let ident = sig.ident.clone();
+    let inner = format_ident!("__{}", ident);
+
+    let mut lets = Vec::new();
+    let mut args = Vec::new();
+
+    for (idx, input) in sig.inputs.iter_mut().enumerate() {
+        let FnArg::Typed(arg) = input else {
+            return Err(syn::Error::new_spanned(
+                input,
+                "`#[provider]` functions can't take `self`",
+            )
+            .into());
+        };
+
+        // Bindings are generated rather than taken from the parameter names, so that a parameter
+        // named `i` can't shadow the container for the arguments that follow it
+        let binding = format_ident!("__arg{}", idx);
+
+        // Every argument is injected, so a bare argument is treated like `#[inject]`
+        let attrs = parse_field_attrs(&arg.attrs)?.unwrap_or_default();
+        arg.attrs
+            .retain(|attr| !attr.meta.path().is_ident("inject"));
+
+        let expr = if is_inject(&arg.ty) {
+            quote! { i.clone() }
+        } else {
+            let value = expand_value(&arg.ty, attrs)?;
+
+            if let Value::Config(ty) = &value {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    "`config` isn't supported for `#[provider]` functions",
+                )
+                .into());
+            }
+
+            value.to_tokens()
+        };
+
+        lets.push(quote! { let #binding = #expr; });
+        args.push(binding);
+    }
+
+    let output = match &sig.output {
+        ReturnType::Type(_, ty) => ty.as_ref().clone(),
+        ReturnType::Default => {
+            return Err(syn::Error::new_spanned(
+                &sig,
+                "`#[provider]` functions must return the provided value",
+            )
+            .into())
+        }
+    };
+
+    let call = if sig.asyncness.is_some() {
+        quote! { #inner(#(#args),*).await }
+    } else {
+        quote! { #inner(#(#args),*) }
+    };
+
+    // Results are unwrapped, and the error is converted into a Provider error
+    let (provided, value) = match result_type(&output) {
+        Some(ty) => (
+            ty.clone(),
+            quote! { #call.map_err(#crate_name::to_provider_error)? },
+        ),
+        None => (output.clone(), call),
+    };
+
+    sig.ident = inner.clone();
+
+    // Doc comments describe the generated Provider, and everything else stays on the fn
+    let (docs, attrs): (Vec<_>, Vec<_>) = attrs
+        .into_iter()
+        .partition(|attr| attr.path().is_ident("doc"));
+
+    let expanded = quote! {
+        #(#docs)*
+        #[allow(non_camel_case_types)]
+        #[derive(Clone, Copy, Default)]
+        #vis struct #ident;
+
+        #[allow(clippy::all, clippy::pedantic)]
+        #[#crate_name::async_trait::async_trait]
+        impl #crate_name::Provider<#provided> for #ident {
+            async fn provide(
+                self: ::std::sync::Arc<Self>,
+                i: #crate_name::Inject,
+            ) -> #crate_name::provider::Result<::std::sync::Arc<#provided>> {
+                #(#attrs)*
+                #sig #block
+
+                #(#lets)*
+
+                Ok(::std::sync::Arc::new(#value))
+            }
+        }
+
+        #[allow(clippy::all, clippy::pedantic)]
+        #[#crate_name::async_trait::async_trait]
+        impl #crate_name::Provider<#crate_name::Dependency> for #ident {
+            async fn provide(
+                self: ::std::sync::Arc<Self>,
+                i: #crate_name::Inject,
+            ) -> #crate_name::provider::Result<::std::sync::Arc<#crate_name::Dependency>> {
+                let provider = self as ::std::sync::Arc<dyn #crate_name::Provider<#provided>>;
+
+                Ok(provider.provide(i).await?)
+            }
+        }
+    };
+
+    Ok(expanded.into())
+}
+
+// Check whether an argument asks for the container itself
+fn is_inject(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Inject" && segment.arguments.is_empty()),
+        _ => false,
+    }
+}
+
+// Find the value type of a `Result<T>` or `Result<T, E>` return type
+fn result_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Result" {
+        return None;
+    }
+
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+
+    match args.args.first() {
+        Some(GenericArgument::Type(inner)) => Some(inner),
+        _ => None,
+    }
+}
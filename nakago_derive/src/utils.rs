@@ -36,6 +36,29 @@ pub fn get_crate_name(internal: bool) -> proc_macro2::TokenStream {
     }
 }
 
+pub fn get_figment_crate_name() -> proc_macro2::TokenStream {
+    match crate_name("nakago-figment") {
+        Ok(FoundCrate::Itself) => quote! { crate },
+        Ok(FoundCrate::Name(name)) => {
+            let ident = Ident::new(&name, Span::call_site());
+            quote! { ::#ident }
+        }
+        Err(_) => quote! { ::nakago_figment },
+    }
+}
+
+/// The path to a dependency of the calling crate, which may have been renamed in its manifest
+pub fn get_package_crate_name(package: &str) -> proc_macro2::TokenStream {
+    let name = match crate_name(package) {
+        Ok(FoundCrate::Itself) => return quote! { crate },
+        Ok(FoundCrate::Name(name)) => name,
+        Err(_) => package.replace('-', "_"),
+    };
+
+    let ident = Ident::new(&name, Span::call_site());
+    quote! { ::#ident }
+}
+
 pub fn get_type_path_and_name(ty: &Type) -> GeneratorResult<(&Type, String)> {
     match ty {
         Type::Path(path) => Ok((
@@ -115,6 +138,21 @@ where
     Ok(())
 }
 
+pub(crate) fn combine_attribute<K, T>(a: &mut Option<(K, T)>, b: Option<(K, T)>) -> syn::Result<()>
+where
+    K: ToTokens,
+{
+    if let Some((kw, inner)) = b {
+        if a.is_some() {
+            let kw_name = std::any::type_name::<K>().split("::").last().unwrap();
+            let msg = format!("`{kw_name}` specified more than once");
+            return Err(syn::Error::new_spanned(kw, msg));
+        }
+        *a = Some((kw, inner));
+    }
+    Ok(())
+}
+
 pub(crate) fn parse_attrs<T>(ident: &str, attrs: &[syn::Attribute]) -> syn::Result<T>
 where
     T: Combine + Default + Parse,
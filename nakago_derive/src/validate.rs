@@ -0,0 +1,215 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parenthesized,
+    parse::{Parse, ParseStream},
+    Expr, ItemStruct, Path, Token,
+};
+
+use crate::utils::{
+    combine_attribute, combine_unary_attribute, get_figment_crate_name, parse_attrs, Combine,
+};
+
+pub(crate) fn expand(item: ItemStruct) -> syn::Result<TokenStream> {
+    let figment = get_figment_crate_name();
+    let ident = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+
+    let StructAttrs { custom } = parse_attrs("validate", &item.attrs)?;
+
+    let mut checks = Vec::new();
+    for field in &item.fields {
+        let FieldAttrs {
+            url,
+            non_empty,
+            nested,
+            range,
+        } = parse_attrs("validate", &field.attrs)?;
+
+        if url.is_none() && non_empty.is_none() && nested.is_none() && range.is_none() {
+            continue;
+        }
+
+        let Some(member) = &field.ident else {
+            return Err(syn::Error::new_spanned(
+                field,
+                "`#[validate]` can only be used on named fields",
+            ));
+        };
+
+        let name = member.to_string();
+
+        if url.is_some() {
+            checks.push(quote! { violations.url(#name, &self.#member); });
+        }
+
+        if non_empty.is_some() {
+            checks.push(quote! { violations.non_empty(#name, &self.#member); });
+        }
+
+        if let Some((_, Range { min, max })) = range {
+            let min = min.map_or_else(
+                || quote! { ::std::option::Option::None },
+                |min| quote! { ::std::option::Option::Some(#min) },
+            );
+            let max = max.map_or_else(
+                || quote! { ::std::option::Option::None },
+                |max| quote! { ::std::option::Option::Some(#max) },
+            );
+
+            checks.push(quote! { violations.range(#name, &self.#member, #min, #max); });
+        }
+
+        if nested.is_some() {
+            checks.push(quote! {
+                violations.nested(#name, |violations| {
+                    #figment::validate::Validate::validate(&self.#member, violations)
+                });
+            });
+        }
+    }
+
+    // Cross-field rules are handled by a function that receives the whole struct
+    if let Some((_, custom)) = custom {
+        checks.push(quote! { #custom(self, violations); });
+    }
+
+    Ok(quote! {
+        impl #impl_generics #figment::validate::Validate for #ident #ty_generics #where_clause {
+            fn validate(&self, violations: &mut #figment::validate::Violations) {
+                #(#checks)*
+            }
+        }
+    })
+}
+
+mod kw {
+    syn::custom_keyword!(custom);
+    syn::custom_keyword!(url);
+    syn::custom_keyword!(non_empty);
+    syn::custom_keyword!(nested);
+    syn::custom_keyword!(range);
+    syn::custom_keyword!(min);
+    syn::custom_keyword!(max);
+}
+
+#[derive(Default)]
+struct StructAttrs {
+    custom: Option<(kw::custom, Path)>,
+}
+
+impl Parse for StructAttrs {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let mut custom = None;
+
+        while !input.is_empty() {
+            let lh = input.lookahead1();
+            if lh.peek(kw::custom) {
+                let kw = input.parse()?;
+                input.parse::<Token![=]>()?;
+                custom = Some((kw, input.parse()?));
+            } else {
+                return Err(lh.error());
+            }
+
+            let _ = input.parse::<Token![,]>();
+        }
+
+        Ok(Self { custom })
+    }
+}
+
+impl Combine for StructAttrs {
+    fn combine(mut self, other: Self) -> syn::Result<Self> {
+        let Self { custom } = other;
+        combine_attribute(&mut self.custom, custom)?;
+        Ok(self)
+    }
+}
+
+struct Range {
+    min: Option<Expr>,
+    max: Option<Expr>,
+}
+
+impl Parse for Range {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let mut min = None;
+        let mut max = None;
+
+        while !input.is_empty() {
+            let lh = input.lookahead1();
+            if lh.peek(kw::min) {
+                input.parse::<kw::min>()?;
+                input.parse::<Token![=]>()?;
+                min = Some(input.parse()?);
+            } else if lh.peek(kw::max) {
+                input.parse::<kw::max>()?;
+                input.parse::<Token![=]>()?;
+                max = Some(input.parse()?);
+            } else {
+                return Err(lh.error());
+            }
+
+            let _ = input.parse::<Token![,]>();
+        }
+
+        if min.is_none() && max.is_none() {
+            return Err(input.error("`range` requires a `min`, a `max`, or both"));
+        }
+
+        Ok(Self { min, max })
+    }
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    url: Option<kw::url>,
+    non_empty: Option<kw::non_empty>,
+    nested: Option<kw::nested>,
+    range: Option<(kw::range, Range)>,
+}
+
+impl Parse for FieldAttrs {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let mut attrs = Self::default();
+
+        while !input.is_empty() {
+            let lh = input.lookahead1();
+            if lh.peek(kw::url) {
+                attrs.url = Some(input.parse()?);
+            } else if lh.peek(kw::non_empty) {
+                attrs.non_empty = Some(input.parse()?);
+            } else if lh.peek(kw::nested) {
+                attrs.nested = Some(input.parse()?);
+            } else if lh.peek(kw::range) {
+                let kw = input.parse()?;
+                let content;
+                parenthesized!(content in input);
+                attrs.range = Some((kw, content.parse()?));
+            } else {
+                return Err(lh.error());
+            }
+
+            let _ = input.parse::<Token![,]>();
+        }
+
+        Ok(attrs)
+    }
+}
+
+impl Combine for FieldAttrs {
+    fn combine(mut self, other: Self) -> syn::Result<Self> {
+        let Self {
+            url,
+            non_empty,
+            nested,
+            range,
+        } = other;
+        combine_unary_attribute(&mut self.url, url)?;
+        combine_unary_attribute(&mut self.non_empty, non_empty)?;
+        combine_unary_attribute(&mut self.nested, nested)?;
+        combine_attribute(&mut self.range, range)?;
+        Ok(self)
+    }
+}
@@ -0,0 +1,372 @@
+use std::{ffi::OsString, fmt::Write as _, marker::PhantomData, path::PathBuf};
+
+use figment::{
+    util::nest,
+    value::{Dict, Map, Value},
+    Figment, Metadata, Profile, Provider, Source,
+};
+
+use crate::{env::parse_value, report::Report, Config};
+
+/// An error parsing the Config command-line arguments
+#[derive(Debug, thiserror::Error)]
+pub enum ArgsError {
+    /// A flag was given without its value
+    #[error("{0} requires a value")]
+    MissingValue(String),
+
+    /// An override wasn't in the `key=value` form
+    #[error("--set expects key=value, found {0:?}")]
+    InvalidOverride(String),
+
+    /// An override didn't match a key in the Config
+    #[error("--set {0}: unknown config key")]
+    UnknownKey(String),
+
+    /// A Config argument wasn't valid unicode
+    #[error("{0:?} must be valid unicode")]
+    NotUnicode(String),
+}
+
+/// The Config arguments from the command line: `-c/--config <FILE>` for a custom path and
+/// `--set key=value` for overrides, which take precedence over every other source. Any other
+/// arguments are left in `rest` for the application to parse.
+///
+/// Pass the Args to `Init::with_args` (and `Watch::with_args`) to apply them.
+pub struct Args<C: Config> {
+    /// The custom config file path
+    pub config: Option<PathBuf>,
+
+    /// The `--set` overrides, in order
+    pub overrides: Vec<Override>,
+
+    /// Whether `-h` or `--help` was given
+    pub help: bool,
+
+    /// The arguments that aren't handled here
+    pub rest: Vec<OsString>,
+
+    _phantom: PhantomData<C>,
+}
+
+impl<C: Config> Default for Args<C> {
+    fn default() -> Self {
+        Self {
+            config: None,
+            overrides: Vec::new(),
+            help: false,
+            rest: Vec::new(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<C: Config> Args<C> {
+    /// Parse the arguments of the current process
+    pub fn parse() -> Result<Self, ArgsError> {
+        Self::from_args(std::env::args_os().skip(1))
+    }
+
+    /// Parse the given arguments, not including the program name
+    pub fn from_args<I, T>(args: I) -> Result<Self, ArgsError>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString>,
+    {
+        let tree = serde_json::to_value(C::default()).unwrap_or(serde_json::Value::Null);
+
+        let mut parsed = Self::default();
+        let mut args = args.into_iter().map(Into::into);
+
+        while let Some(arg) = args.next() {
+            let Some(text) = arg.to_str() else {
+                parsed.rest.push(arg);
+                continue;
+            };
+
+            let (flag, inline) = match text.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (text, None),
+            };
+
+            match flag {
+                "-h" | "--help" => parsed.help = true,
+                "-c" | "--config" => {
+                    let value = value(flag, inline, &mut args)?;
+
+                    parsed.config = Some(PathBuf::from(value));
+                }
+                "--set" => {
+                    let value = value(flag, inline, &mut args)?;
+
+                    let Some((key, raw)) = value.split_once('=') else {
+                        return Err(ArgsError::InvalidOverride(value));
+                    };
+
+                    let key = key.trim();
+                    if key.is_empty() || !is_known(&tree, key) {
+                        return Err(ArgsError::UnknownKey(key.to_string()));
+                    }
+
+                    parsed.overrides.push(Override::new(key, raw));
+                }
+                _ => parsed.rest.push(arg),
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// Use a custom config file path
+    pub fn with_config(self, config: PathBuf) -> Self {
+        Self {
+            config: Some(config),
+            ..self
+        }
+    }
+
+    /// Add an override, as if `--set key=value` was given
+    pub fn with_override(mut self, key: &str, value: &str) -> Self {
+        self.overrides.push(Override::new(key, value));
+        self
+    }
+
+    /// The usage for the Config arguments, followed by each known key and its default value
+    pub fn help() -> String {
+        let mut help = String::from(
+            "Config options:\n  -c, --config <FILE>  Load a custom config file\n  \
+             --set <KEY>=<VALUE>  Override a config value, such as --set http.port=8080\n",
+        );
+
+        let report = Report::new(&C::default(), &Figment::new());
+
+        if !report.entries.is_empty() {
+            help.push_str("\nConfig keys:\n");
+
+            for entry in &report.entries {
+                let _ = writeln!(help, "  {} = {}", entry.key, entry.value);
+            }
+        }
+
+        help
+    }
+}
+
+// Whether the dotted key is present in the Config, or falls within a map-shaped section such as
+// `Named`, which has no entries by default
+fn is_known(tree: &serde_json::Value, key: &str) -> bool {
+    let mut value = tree;
+
+    for part in key.split('.') {
+        let Some(object) = value.as_object() else {
+            return false;
+        };
+
+        if object.is_empty() {
+            return true;
+        }
+
+        match object.get(part) {
+            Some(child) => value = child,
+            None => return false,
+        }
+    }
+
+    true
+}
+
+// The value for a flag, either inline after an `=` or as the following argument
+fn value(
+    flag: &str,
+    inline: Option<String>,
+    args: &mut impl Iterator<Item = OsString>,
+) -> Result<String, ArgsError> {
+    if let Some(value) = inline {
+        return Ok(value);
+    }
+
+    let value = args
+        .next()
+        .ok_or_else(|| ArgsError::MissingValue(flag.to_string()))?;
+
+    value
+        .into_string()
+        .map_err(|value| ArgsError::NotUnicode(format!("{flag} {}", value.to_string_lossy())))
+}
+
+/// A single `--set key=value` override. The value is always parsed like an environment variable,
+/// regardless of the type at the key, so `8080` is an integer, `true` is a boolean and `[1, 2]` is
+/// an array. Wrap the value in double quotes to keep it a string, such as `--set 'pass="0123"'`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Override {
+    /// The dotted key, such as `http.port`
+    pub key: String,
+
+    /// The raw value
+    pub value: String,
+}
+
+impl Override {
+    /// Create a new Override for a dotted key
+    pub fn new(key: &str, value: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+impl Provider for Override {
+    fn metadata(&self) -> Metadata {
+        Metadata::from(
+            "Command-line argument",
+            Source::Custom(format!("--set {}", self.key)),
+        )
+    }
+
+    fn data(&self) -> figment::Result<Map<Profile, Dict>> {
+        match nest(&self.key, parse_value(&self.value)) {
+            Value::Dict(_, dict) => Ok(Profile::Default.collect(dict)),
+            _ => Ok(Map::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use nakago::Inject;
+    use nakago_derive::Config;
+    use pretty_assertions::assert_eq;
+    use serde::{Deserialize, Serialize};
+
+    use crate::Init;
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+    pub struct Http {
+        pub port: u16,
+        pub address: String,
+    }
+
+    impl Default for Http {
+        fn default() -> Self {
+            Self {
+                port: 3000,
+                address: "0.0.0.0".to_string(),
+            }
+        }
+    }
+
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Config)]
+    pub struct Server {
+        pub http: Http,
+        pub debug: bool,
+    }
+
+    #[test]
+    fn test_args_parse() -> anyhow::Result<()> {
+        let args = Args::<Server>::from_args([
+            "--print-config",
+            "-c",
+            "config.toml",
+            "--set",
+            "http.port=8080",
+            "--set=debug=true",
+            "--help",
+        ])?;
+
+        assert!(args.help);
+        assert_eq!(Some(PathBuf::from("config.toml")), args.config);
+        assert_eq!(
+            vec![
+                Override::new("http.port", "8080"),
+                Override::new("debug", "true")
+            ],
+            args.overrides
+        );
+        assert_eq!(vec![OsString::from("--print-config")], args.rest);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_override_values() -> anyhow::Result<()> {
+        let value = |raw: &str| -> anyhow::Result<Option<Value>> {
+            let data = Override::new("value", raw).data()?;
+
+            Ok(data
+                .get(&Profile::Default)
+                .and_then(|dict| dict.get("value"))
+                .cloned())
+        };
+
+        assert_eq!(Some(Value::from(8080_usize)), value("8080")?);
+        assert_eq!(Some(Value::from(true)), value("true")?);
+        assert_eq!(Some(Value::from("localhost")), value("localhost")?);
+
+        // Quoted values are kept as strings
+        assert_eq!(Some(Value::from("true")), value("\"true\"")?);
+        assert_eq!(Some(Value::from("0123")), value("\"0123\"")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_args_errors() {
+        let result = Args::<Server>::from_args(["--set", "http.host=localhost"]);
+        assert!(matches!(result, Err(ArgsError::UnknownKey(key)) if key == "http.host"));
+
+        let result = Args::<Server>::from_args(["--set", "http.port"]);
+        assert!(matches!(result, Err(ArgsError::InvalidOverride(_))));
+
+        let result = Args::<Server>::from_args(["--config"]);
+        assert!(matches!(result, Err(ArgsError::MissingValue(flag)) if flag == "--config"));
+    }
+
+    #[test]
+    fn test_args_help() {
+        let help = Args::<Server>::help();
+
+        assert!(help.contains("--set <KEY>=<VALUE>"));
+        assert!(help.contains("  debug = false\n"));
+        assert!(help.contains("  http.port = 3000\n"));
+    }
+
+    #[tokio::test]
+    async fn test_init_with_args() -> anyhow::Result<()> {
+        let i = Inject::default();
+
+        let path = std::env::temp_dir().join(format!("nakago-args-{}.toml", ulid::Ulid::new()));
+        std::fs::write(
+            &path,
+            "debug = true\n[http]\nport = 4000\naddress = \"127.0.0.1\"\n",
+        )?;
+
+        let args = Args::<Server>::from_args([
+            OsString::from("--config"),
+            path.clone().into_os_string(),
+            OsString::from("--set"),
+            OsString::from("http.port=8080"),
+        ])?;
+
+        Init::<Server>::default().with_args(&args).init(&i).await?;
+
+        // The overrides take precedence over the custom path
+        let config = i.get::<Server>().await?;
+        assert_eq!(8080, config.http.port);
+        assert_eq!("127.0.0.1", config.http.address);
+        assert!(config.debug);
+
+        let report = i.get::<Report>().await?;
+        let source = report.get("http.port").and_then(|e| e.source.clone());
+        assert_eq!(
+            Some("Command-line argument --set http.port".to_string()),
+            source
+        );
+
+        std::fs::remove_file(path)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,283 @@
+use std::{any::Any, fmt::Display, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use derive_new::new;
+use figment::Figment;
+use nakago::{Inject, Result, Tag};
+use tokio::time;
+
+/// How long each AsyncLoader may run before it is abandoned, unless configured otherwise
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An AsyncLoader augments the Config with values that must be fetched, such as from a secrets
+/// agent, an HTTP config endpoint or a database table of runtime settings. Unlike a `Loader`, it
+/// may await and it may fail.
+///
+/// AsyncLoaders run in order after the files and the `Loader` stack, so their values take
+/// precedence.
+#[async_trait]
+pub trait AsyncLoader: Any + Send + Sync {
+    /// The name used for this Loader in errors, which defaults to the type name
+    fn name(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
+
+    /// Apply transformations to the Figment provider, or fail with the reason
+    async fn load(&self, figment: Figment) -> anyhow::Result<Figment>;
+}
+
+/// AsyncLoaders is a convenience type for a collection of AsyncLoader instances
+pub type AsyncLoaders = Vec<Arc<dyn AsyncLoader>>;
+
+/// An AsyncLoader that failed or didn't finish in time
+#[derive(Debug, thiserror::Error)]
+#[error("config loader {loader} {reason}")]
+pub struct LoaderError {
+    /// The name of the Loader
+    pub loader: String,
+
+    /// What went wrong
+    pub reason: Reason,
+}
+
+/// The reason an AsyncLoader was rejected
+#[derive(Debug)]
+pub enum Reason {
+    /// The Loader returned an error
+    Failed(anyhow::Error),
+
+    /// The Loader didn't finish within the timeout
+    TimedOut(Duration),
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reason::Failed(err) => write!(f, "failed: {err:#}"),
+            Reason::TimedOut(timeout) => write!(f, "timed out after {timeout:?}"),
+        }
+    }
+}
+
+/// Run each AsyncLoader in order, stopping at the first one that fails or times out
+pub async fn run(
+    loaders: &AsyncLoaders,
+    mut figment: Figment,
+    timeout: Duration,
+) -> std::result::Result<Figment, LoaderError> {
+    for loader in loaders {
+        figment = match time::timeout(timeout, loader.load(figment)).await {
+            Ok(Ok(figment)) => figment,
+            Ok(Err(err)) => {
+                return Err(LoaderError {
+                    loader: loader.name(),
+                    reason: Reason::Failed(err),
+                })
+            }
+            Err(_) => {
+                return Err(LoaderError {
+                    loader: loader.name(),
+                    reason: Reason::TimedOut(timeout),
+                })
+            }
+        };
+    }
+
+    Ok(figment)
+}
+
+/// Add the given AsyncLoaders to the stack currently in the Inject container
+#[derive(Default, new)]
+pub struct Add {
+    tag: Option<&'static Tag<AsyncLoaders>>,
+}
+
+impl Add {
+    /// Use a Tag when injecting the AsyncLoaders
+    pub fn with_tag(self, tag: &'static Tag<AsyncLoaders>) -> Self {
+        Self { tag: Some(tag) }
+    }
+
+    /// Add the given AsyncLoaders to the stack currently in the Inject container
+    pub async fn loaders(&self, i: &Inject, loaders: AsyncLoaders) -> Result<()> {
+        let current_result = match self.tag {
+            Some(tag) => i.consume_tag(tag).await,
+            None => i.consume::<AsyncLoaders>().await,
+        };
+
+        let mut current = current_result.unwrap_or_default();
+        current.extend(loaders);
+
+        match self.tag {
+            Some(tag) => i.inject_tag(tag, current).await?,
+            None => i.inject::<AsyncLoaders>(current).await?,
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use anyhow::anyhow;
+    use figment::providers::{Format, Json};
+    use nakago::Error;
+    use nakago_derive::Config;
+    use pretty_assertions::assert_eq;
+    use serde::{Deserialize, Serialize};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+
+    use crate::Init;
+
+    use super::*;
+
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Config)]
+    pub struct Runtime {
+        pub level: String,
+        pub limit: u32,
+    }
+
+    /// Fetches a JSON document from a config server
+    pub struct Remote {
+        addr: String,
+    }
+
+    #[async_trait]
+    impl AsyncLoader for Remote {
+        fn name(&self) -> String {
+            format!("remote({})", self.addr)
+        }
+
+        async fn load(&self, figment: Figment) -> anyhow::Result<Figment> {
+            let mut stream = TcpStream::connect(&self.addr).await?;
+
+            let mut body = String::new();
+            stream.read_to_string(&mut body).await?;
+
+            Ok(figment.merge(Json::string(&body)))
+        }
+    }
+
+    pub struct Failing {}
+
+    #[async_trait]
+    impl AsyncLoader for Failing {
+        async fn load(&self, _figment: Figment) -> anyhow::Result<Figment> {
+            Err(anyhow!("the agent is unavailable"))
+        }
+    }
+
+    pub struct Hanging {}
+
+    #[async_trait]
+    impl AsyncLoader for Hanging {
+        fn name(&self) -> String {
+            "hanging".to_string()
+        }
+
+        async fn load(&self, figment: Figment) -> anyhow::Result<Figment> {
+            time::sleep(Duration::from_secs(60)).await;
+
+            Ok(figment)
+        }
+    }
+
+    // Serve a single JSON document to each connection
+    async fn serve(body: &'static str) -> anyhow::Result<String> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?.to_string();
+
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let _ = stream.write_all(body.as_bytes()).await;
+            }
+        });
+
+        Ok(addr)
+    }
+
+    fn loader_error(result: Result<()>) -> Arc<anyhow::Error> {
+        let Err(Error::Any(err)) = result else {
+            panic!("did not return Err as expected")
+        };
+
+        err
+    }
+
+    #[tokio::test]
+    async fn test_init_async_loaders_success() -> anyhow::Result<()> {
+        let i = Inject::default();
+
+        let first = serve(r#"{"level": "warn", "limit": 5}"#).await?;
+        let second = serve(r#"{"limit": 10}"#).await?;
+
+        Add::default()
+            .loaders(
+                &i,
+                vec![
+                    Arc::new(Remote { addr: first }),
+                    Arc::new(Remote { addr: second }),
+                ],
+            )
+            .await?;
+
+        Init::<Runtime>::default().init(&i).await?;
+
+        // Later loaders take precedence
+        let config = i.get::<Runtime>().await?;
+        assert_eq!("warn", config.level);
+        assert_eq!(10, config.limit);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_init_async_loader_failed() -> anyhow::Result<()> {
+        let i = Inject::default();
+
+        let addr = serve(r#"{"level": "warn"}"#).await?;
+
+        Add::default()
+            .loaders(&i, vec![Arc::new(Remote { addr }), Arc::new(Failing {})])
+            .await?;
+
+        let err = loader_error(Init::<Runtime>::default().init(&i).await);
+
+        let loader_error = err
+            .downcast_ref::<LoaderError>()
+            .expect("expected a LoaderError");
+        assert!(loader_error.loader.ends_with("Failing"));
+        assert!(err.to_string().contains("failed: the agent is unavailable"));
+
+        // The Config is not injected
+        assert!(i.get_opt::<Runtime>().await?.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_init_async_loader_timeout() -> anyhow::Result<()> {
+        let i = Inject::default();
+
+        Add::default()
+            .loaders(&i, vec![Arc::new(Hanging {})])
+            .await?;
+
+        let err = loader_error(
+            Init::<Runtime>::default()
+                .with_timeout(Duration::from_millis(10))
+                .init(&i)
+                .await,
+        );
+
+        assert_eq!(
+            "config loader hanging timed out after 10ms",
+            err.to_string()
+        );
+
+        Ok(())
+    }
+}
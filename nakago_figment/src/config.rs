@@ -2,8 +2,18 @@ use std::{any::Any, fmt::Debug};
 
 use serde::{Deserialize, Serialize};
 
+use crate::{env, validate::Violations};
+
 /// Config is the final loaded result
 pub trait Config:
     Any + Clone + Debug + Default + Serialize + Send + Sync + for<'a> Deserialize<'a>
 {
+    /// Options for mapping environment variables onto the nested keys of the Config
+    fn env_options() -> env::Options {
+        env::Options::default()
+    }
+
+    /// Check the loaded Config, adding any problems to the given Violations. This runs after the
+    /// Config is extracted by `Init`, and does nothing by default.
+    fn validate(&self, _violations: &mut Violations) {}
 }
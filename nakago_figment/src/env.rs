@@ -0,0 +1,398 @@
+use std::{env, marker::PhantomData, sync::Arc};
+
+use figment::{
+    util::nest,
+    value::{Dict, Map, Uncased, UncasedStr},
+    Figment, Metadata, Profile, Provider,
+};
+use nakago::{Inject, Result, Tag};
+use serde_json::Value;
+
+use crate::{loaders, Config, Loaders};
+
+/// Options for mapping environment variables onto the nested keys of a Config
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// An optional prefix that every variable must start with, such as `APP`
+    pub prefix: Option<&'static str>,
+
+    /// The separator between nested keys in variable names, which defaults to `_`
+    pub separator: &'static str,
+
+    /// Variable prefixes to use in place of the uppercased field name for top-level sections, as
+    /// (prefix, field) pairs
+    pub overrides: Vec<(&'static str, &'static str)>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            prefix: None,
+            separator: "_",
+            overrides: Vec::new(),
+        }
+    }
+}
+
+/// A Config Loader that maps environment variables onto the keys of the Config, using the nesting
+/// of the Config's default value. With the default options, `DATABASE_POOL_MAX` is mapped to
+/// `database.pool.max` and `AUTH_CLIENT_ID` is mapped to `auth.client.id`. Variables that don't
+/// match a top-level section of the Config are ignored.
+pub struct Loader<C: Config> {
+    _phantom: PhantomData<C>,
+}
+
+impl<C: Config> Default for Loader<C> {
+    fn default() -> Self {
+        Self {
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<C: Config> Loader<C> {
+    // Merge the given environment variables that map onto a key of the Config
+    fn load_vars(figment: Figment, vars: impl IntoIterator<Item = (String, String)>) -> Figment {
+        let tree = serde_json::to_value(C::default()).unwrap_or(Value::Null);
+        let options = C::env_options();
+
+        vars.into_iter().fold(figment, |figment, (name, value)| {
+            let key = UncasedStr::new(&name);
+
+            // Variables that point to a secret file are handled by the secrets Loader
+            if file_target(&tree, &options, key).is_some() {
+                return figment;
+            }
+
+            match map_key(&tree, &options, key) {
+                Some(key) => figment.merge(Var {
+                    key: key.to_string(),
+                    name,
+                    value,
+                }),
+                None => figment,
+            }
+        })
+    }
+}
+
+impl<C: Config> crate::Loader for Loader<C> {
+    fn load(&self, figment: Figment) -> Figment {
+        let vars = env::vars_os().filter_map(|(name, value)| {
+            Some((name.into_string().ok()?, value.into_string().ok()?))
+        });
+
+        Self::load_vars(figment, vars)
+    }
+}
+
+// A single environment variable, mapped onto a dotted Config key
+struct Var {
+    name: String,
+    key: String,
+    value: String,
+}
+
+impl Provider for Var {
+    fn metadata(&self) -> Metadata {
+        Metadata::named(format!("`{}` environment variable", self.name))
+    }
+
+    fn data(&self) -> figment::Result<Map<Profile, Dict>> {
+        let dict = nest(&self.key, parse_value(&self.value))
+            .into_dict()
+            .unwrap_or_default();
+
+        Ok(Profile::Default.collect(dict))
+    }
+}
+
+/// Add the environment variable Loader for the given Config to the stack.
+pub async fn add_loader<C: Config>(i: &Inject) -> Result<()> {
+    loaders::Add::default()
+        .loaders(i, vec![Arc::<Loader<C>>::default()])
+        .await
+}
+
+/// Add the environment variable Loader for the given Config to the stack.
+pub async fn add_loader_with_tag<C: Config>(i: &Inject, tag: &'static Tag<Loaders>) -> Result<()> {
+    loaders::Add::default()
+        .with_tag(tag)
+        .loaders(i, vec![Arc::<Loader<C>>::default()])
+        .await
+}
+
+/// The suffix of variables that name a file containing the value, such as `DATABASE_URL_FILE`
+pub const FILE_SUFFIX: &str = "_FILE";
+
+// Map a variable with the `_FILE` suffix to the dotted Config key that the file's contents belong
+// to, if the key without the suffix is present in the Config
+pub(crate) fn file_target(
+    tree: &Value,
+    options: &Options,
+    key: &UncasedStr,
+) -> Option<Uncased<'static>> {
+    let name = key.as_str().to_ascii_uppercase();
+    let stripped = name.strip_suffix(FILE_SUFFIX)?;
+
+    let target = map_key(tree, options, UncasedStr::new(stripped))?;
+
+    find(tree, target.as_str()).map(|_| target)
+}
+
+// Parse a raw string into a Config value the way figment parses environment variables, so that
+// `10` becomes a number and `[a, b]` becomes an array. Anything that doesn't parse is a string.
+pub(crate) fn parse_value(raw: &str) -> figment::value::Value {
+    match raw.parse() {
+        Ok(value) => value,
+        Err(never) => match never {},
+    }
+}
+
+// Find the value at a dotted key in the Config tree
+pub(crate) fn find<'a>(tree: &'a Value, key: &str) -> Option<&'a Value> {
+    key.split('.')
+        .try_fold(tree, |value, part| value.as_object()?.get(part))
+}
+
+// Map an environment variable name to a dotted Config key, if it belongs to a top-level section
+pub(crate) fn map_key(
+    tree: &Value,
+    options: &Options,
+    key: &UncasedStr,
+) -> Option<Uncased<'static>> {
+    let Value::Object(sections) = tree else {
+        return None;
+    };
+
+    let mut name = key.as_str().to_ascii_uppercase();
+    if let Some(prefix) = options.prefix {
+        name = name
+            .strip_prefix(&format!(
+                "{}{}",
+                prefix.to_ascii_uppercase(),
+                options.separator
+            ))?
+            .to_string();
+    }
+
+    let mut sections: Vec<(String, &String, &Value)> = sections
+        .iter()
+        .map(|(field, value)| {
+            let section = options
+                .overrides
+                .iter()
+                .find(|(_, overridden)| overridden == field)
+                .map_or_else(
+                    || field.to_ascii_uppercase(),
+                    |(env, _)| env.to_ascii_uppercase(),
+                );
+
+            (section, field, value)
+        })
+        .collect();
+
+    // Prefer the longest section name, so that `AUTH_ADMIN_URL` matches `auth_admin` over `auth`
+    sections.sort_by_key(|(section, _, _)| std::cmp::Reverse(section.len()));
+
+    for (section, field, value) in sections {
+        if name == section {
+            return Some(field.clone().into());
+        }
+
+        if let Some(rest) = name.strip_prefix(&format!("{section}{}", options.separator)) {
+            let path = map_path(value, rest, options.separator);
+
+            return Some(format!("{field}.{path}").into());
+        }
+    }
+
+    None
+}
+
+// Map the remainder of a variable name within a section, preferring the longest known key at each
+// level so that keys containing the separator are matched correctly
+fn map_path(tree: &Value, name: &str, separator: &str) -> String {
+    if let Value::Object(children) = tree {
+        let mut keys: Vec<(&String, &Value)> = children.iter().collect();
+        keys.sort_by_key(|(key, _)| std::cmp::Reverse(key.len()));
+
+        for (key, value) in keys {
+            let upper = key.to_ascii_uppercase();
+
+            if name == upper {
+                return key.clone();
+            }
+
+            if let Some(rest) = name.strip_prefix(&format!("{upper}{separator}")) {
+                if value.is_object() {
+                    return format!("{key}.{}", map_path(value, rest, separator));
+                }
+            }
+        }
+    }
+
+    // Fall back to splitting on the separator for keys that aren't present in the defaults
+    name.split(separator)
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use anyhow::Result;
+    use nakago_derive::Config;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+    pub struct Pool {
+        pub min: u32,
+        pub max: u32,
+    }
+
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+    pub struct Database {
+        pub url: String,
+        pub pool: Pool,
+        pub max_lifetime: u64,
+    }
+
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+    pub struct Client {
+        pub id: String,
+    }
+
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+    pub struct Auth {
+        pub client: Client,
+    }
+
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Config)]
+    pub struct AppConfig {
+        pub database: Database,
+        pub auth: Auth,
+    }
+
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Config)]
+    #[config(prefix = "NAKAGO_ENV_TEST", separator = "__")]
+    pub struct PrefixedConfig {
+        #[config(env = "DB")]
+        pub database: Database,
+
+        #[from_ref(skip)]
+        pub name: String,
+    }
+
+    fn map(tree: &Value, options: &Options, key: &str) -> Option<String> {
+        map_key(tree, options, key.into()).map(|key| key.as_str().to_string())
+    }
+
+    #[test]
+    fn test_map_key_nested() -> Result<()> {
+        let tree = serde_json::to_value(AppConfig::default())?;
+        let options = AppConfig::env_options();
+
+        assert_eq!(
+            Some("database.pool.max".to_string()),
+            map(&tree, &options, "DATABASE_POOL_MAX")
+        );
+        assert_eq!(
+            Some("database.max_lifetime".to_string()),
+            map(&tree, &options, "DATABASE_MAX_LIFETIME")
+        );
+        assert_eq!(
+            Some("auth.client.id".to_string()),
+            map(&tree, &options, "AUTH_CLIENT_ID")
+        );
+        assert_eq!(None, map(&tree, &options, "PATH"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_key_overlapping_sections() {
+        let tree = serde_json::json!({
+            "auth": { "url": "" },
+            "auth_admin": { "url": "" },
+        });
+        let options = AppConfig::env_options();
+
+        assert_eq!(
+            Some("auth_admin.url".to_string()),
+            map(&tree, &options, "AUTH_ADMIN_URL")
+        );
+        assert_eq!(
+            Some("auth.url".to_string()),
+            map(&tree, &options, "AUTH_URL")
+        );
+    }
+
+    #[test]
+    fn test_map_key_options() -> Result<()> {
+        let tree = serde_json::to_value(PrefixedConfig::default())?;
+        let options = PrefixedConfig::env_options();
+
+        assert_eq!(
+            Some("database.pool.min".to_string()),
+            map(&tree, &options, "NAKAGO_ENV_TEST__DB__POOL__MIN")
+        );
+        assert_eq!(
+            Some("name".to_string()),
+            map(&tree, &options, "NAKAGO_ENV_TEST__NAME")
+        );
+        assert_eq!(None, map(&tree, &options, "DB__POOL__MIN"));
+        assert_eq!(None, map(&tree, &options, "NAKAGO_ENV_TEST__DATABASE__URL"));
+
+        Ok(())
+    }
+
+    // Load with the given environment variables rather than the real ones, which other tests read
+    // in parallel
+    fn load_vars(vars: Vec<(&str, &str)>) -> Result<PrefixedConfig> {
+        let figment = Figment::from(figment::providers::Serialized::defaults(
+            PrefixedConfig::default(),
+        ));
+        let vars = vars
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()));
+
+        Ok(Loader::<PrefixedConfig>::load_vars(figment, vars).extract()?)
+    }
+
+    #[test]
+    fn test_loader_success() -> Result<()> {
+        let config = load_vars(vec![
+            ("NAKAGO_ENV_TEST__DB__URL", "postgres://localhost/test"),
+            ("NAKAGO_ENV_TEST__DB__POOL__MAX", "10"),
+            ("NAKAGO_ENV_TEST__NAME_FILE", "/run/secrets/name"),
+            ("PATH", "/usr/bin"),
+        ])?;
+
+        assert_eq!("postgres://localhost/test", config.database.url);
+        assert_eq!(10, config.database.pool.max);
+
+        // Variables that point to a secret file are left to the secrets Loader
+        assert_eq!("", config.name);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_derive_config_from_ref() {
+        let config = AppConfig {
+            auth: Auth {
+                client: Client {
+                    id: "client".to_string(),
+                },
+            },
+            ..Default::default()
+        };
+
+        let auth = <Auth as crate::FromRef<AppConfig>>::from_ref(&config);
+
+        assert_eq!("client", auth.client.id);
+    }
+}
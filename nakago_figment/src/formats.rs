@@ -0,0 +1,321 @@
+use std::{
+    any::Any,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use derive_new::new;
+use figment::{
+    error::Kind,
+    value::{Dict, Map},
+    Figment, Metadata, Profile, Provider, Source,
+};
+use nakago::{Inject, Result, Tag};
+
+/// A FileFormat merges Config files with a particular set of extensions, in addition to the
+/// built-in toml, yml, yaml and json formats
+pub trait FileFormat: Any + Send + Sync {
+    /// The file extensions handled by this format, without the leading dot
+    fn extensions(&self) -> Vec<&'static str>;
+
+    /// Merge the Config file at the given path into the Figment provider
+    fn merge(&self, figment: Figment, path: &Path) -> Figment;
+}
+
+/// Formats is a convenience type for a collection of FileFormat instances
+pub type Formats = Vec<Arc<dyn FileFormat>>;
+
+/// Add the given FileFormats to the stack currently in the Inject container
+#[derive(Default, new)]
+pub struct Add {
+    tag: Option<&'static Tag<Formats>>,
+}
+
+impl Add {
+    /// Use a Tag when injecting the Formats
+    pub fn with_tag(self, tag: &'static Tag<Formats>) -> Self {
+        Self { tag: Some(tag) }
+    }
+
+    /// Add the given FileFormats to the stack currently in the Inject container
+    pub async fn formats(&self, i: &Inject, formats: Formats) -> Result<()> {
+        let current_result = match self.tag {
+            Some(tag) => i.consume_tag(tag).await,
+            None => i.consume::<Formats>().await,
+        };
+
+        let mut current = current_result.unwrap_or_default();
+        current.extend(formats);
+
+        match self.tag {
+            Some(tag) => i.inject_tag(tag, current).await?,
+            None => i.inject::<Formats>(current).await?,
+        }
+
+        Ok(())
+    }
+}
+
+/// A Provider that reads a Config file and parses it with the given function
+#[derive(new)]
+pub struct Parsed {
+    name: &'static str,
+    path: PathBuf,
+    parse: fn(&str) -> std::result::Result<Dict, String>,
+}
+
+impl Provider for Parsed {
+    fn metadata(&self) -> Metadata {
+        Metadata::from(self.name, Source::File(self.path.clone()))
+    }
+
+    fn data(&self) -> figment::Result<Map<Profile, Dict>> {
+        // Missing files are skipped, like the built-in formats
+        if !self.path.exists() {
+            return Ok(Map::new());
+        }
+
+        let contents = fs::read_to_string(&self.path).map_err(|err| err.to_string())?;
+
+        let dict = (self.parse)(&contents).map_err(|err| {
+            figment::Error::from(Kind::Message(format!(
+                "{} {}: {err}",
+                self.name,
+                self.path.display()
+            )))
+        })?;
+
+        Ok(Profile::Default.collect(dict))
+    }
+}
+
+/// A Provider that always fails, used to report a Config file that can't be loaded
+#[derive(new)]
+pub(crate) struct Unloadable {
+    path: PathBuf,
+    reason: &'static str,
+}
+
+impl Provider for Unloadable {
+    fn metadata(&self) -> Metadata {
+        Metadata::from("Custom config file", Source::File(self.path.clone()))
+    }
+
+    fn data(&self) -> figment::Result<Map<Profile, Dict>> {
+        Err(Kind::Message(format!(
+            "custom config file {} {}",
+            self.path.display(),
+            self.reason
+        ))
+        .into())
+    }
+}
+
+/// The JSON5 format, for `.json5` files
+#[cfg(feature = "json5")]
+#[derive(Default)]
+pub struct Json5 {}
+
+#[cfg(feature = "json5")]
+impl FileFormat for Json5 {
+    fn extensions(&self) -> Vec<&'static str> {
+        vec!["json5"]
+    }
+
+    fn merge(&self, figment: Figment, path: &Path) -> Figment {
+        figment.merge(Parsed::new("JSON5 file", path.to_path_buf(), |contents| {
+            json5::from_str(contents).map_err(|err| err.to_string())
+        }))
+    }
+}
+
+/// The RON format, for `.ron` files. The top level is a RON map, such as
+/// `{ "http": { "port": 8080 } }`.
+#[cfg(feature = "ron")]
+#[derive(Default)]
+pub struct Ron {}
+
+#[cfg(feature = "ron")]
+impl FileFormat for Ron {
+    fn extensions(&self) -> Vec<&'static str> {
+        vec!["ron"]
+    }
+
+    fn merge(&self, figment: Figment, path: &Path) -> Figment {
+        figment.merge(Parsed::new("RON file", path.to_path_buf(), |contents| {
+            ron::from_str(contents).map_err(|err| err.to_string())
+        }))
+    }
+}
+
+/// The INI-style dotenv format, for `.env` files. Each `KEY=value` line is mapped onto the Config
+/// like the environment variable of the same name.
+#[cfg(feature = "dotenv")]
+pub struct Dotenv<C: crate::Config> {
+    _phantom: std::marker::PhantomData<C>,
+}
+
+#[cfg(feature = "dotenv")]
+impl<C: crate::Config> Default for Dotenv<C> {
+    fn default() -> Self {
+        Self {
+            _phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "dotenv")]
+impl<C: crate::Config> FileFormat for Dotenv<C> {
+    fn extensions(&self) -> Vec<&'static str> {
+        vec!["env"]
+    }
+
+    fn merge(&self, figment: Figment, path: &Path) -> Figment {
+        figment.merge(Parsed::new("dotenv file", path.to_path_buf(), |contents| {
+            let tree = serde_json::to_value(C::default()).unwrap_or(serde_json::Value::Null);
+            let options = C::env_options();
+
+            let mut dict = Dict::new();
+
+            for item in dotenvy::from_read_iter(contents.as_bytes()) {
+                let (name, value) = item.map_err(|err| err.to_string())?;
+
+                let Some(key) =
+                    crate::env::map_key(&tree, &options, figment::value::UncasedStr::new(&name))
+                else {
+                    continue;
+                };
+
+                // Values are parsed like the environment variables they stand in for
+                let value = crate::env::parse_value(&value);
+
+                insert(&mut dict, key.as_str(), value);
+            }
+
+            Ok(dict)
+        }))
+    }
+}
+
+// Insert a value at a dotted key, creating the intermediate dicts as needed
+#[cfg(feature = "dotenv")]
+fn insert(dict: &mut Dict, key: &str, value: figment::value::Value) {
+    use figment::value::Value;
+
+    match key.split_once('.') {
+        None => {
+            dict.insert(key.to_string(), value);
+        }
+        Some((head, rest)) => {
+            let entry = dict
+                .entry(head.to_string())
+                .or_insert_with(|| Dict::new().into());
+
+            if !matches!(entry, Value::Dict(..)) {
+                *entry = Dict::new().into();
+            }
+
+            if let Value::Dict(_, child) = entry {
+                insert(child, rest, value);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, any(feature = "json5", feature = "ron", feature = "dotenv")))]
+pub(crate) mod test {
+    use std::env;
+
+    use nakago_derive::Config;
+    use pretty_assertions::assert_eq;
+    use serde::{Deserialize, Serialize};
+
+    use crate::loader::LoadAll;
+
+    use super::*;
+
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+    pub struct Pool {
+        pub max: u32,
+    }
+
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Config)]
+    pub struct Dotted {
+        pub dotted_url: String,
+        pub dotted_pool: Pool,
+    }
+
+    // Write a temporary Config file with the given extension and extract it with the given format
+    fn extract(
+        format: Arc<dyn FileFormat>,
+        extension: &str,
+        contents: &str,
+    ) -> anyhow::Result<Dotted> {
+        let path =
+            env::temp_dir().join(format!("nakago-formats-{}.{extension}", ulid::Ulid::new()));
+        fs::write(&path, contents)?;
+
+        let result = LoadAll::<Dotted>::new(vec![])
+            .with_formats(vec![format])
+            .extract(Some(path.clone()));
+
+        fs::remove_file(path)?;
+
+        Ok(result?.0)
+    }
+
+    #[cfg(feature = "json5")]
+    #[tokio::test]
+    async fn test_json5_format() -> anyhow::Result<()> {
+        let config = extract(
+            Arc::new(Json5::default()),
+            "json5",
+            "// comment\n{ dotted_url: 'postgres://json5', dotted_pool: { max: 7, }, }\n",
+        )?;
+
+        assert_eq!("postgres://json5", config.dotted_url);
+        assert_eq!(7, config.dotted_pool.max);
+
+        // Parse errors name the file
+        let result = extract(Arc::new(Json5::default()), "json5", "{ dotted_url: ");
+        let err = result.expect_err("did not return Err as expected");
+        assert!(err.to_string().contains("JSON5 file"));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "ron")]
+    #[tokio::test]
+    async fn test_ron_format() -> anyhow::Result<()> {
+        let config = extract(
+            Arc::new(Ron::default()),
+            "ron",
+            "// comment\n{ \"dotted_url\": \"postgres://ron\", \"dotted_pool\": { \"max\": 3 } }\n",
+        )?;
+
+        assert_eq!("postgres://ron", config.dotted_url);
+        assert_eq!(3, config.dotted_pool.max);
+
+        let result = extract(Arc::new(Ron::default()), "ron", "{ \"dotted_url\": ");
+        let err = result.expect_err("did not return Err as expected");
+        assert!(err.to_string().contains("RON file"));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "dotenv")]
+    #[tokio::test]
+    async fn test_dotenv_format() -> anyhow::Result<()> {
+        let config = extract(
+            Arc::new(Dotenv::<Dotted>::default()),
+            "env",
+            "# comment\nDOTTED_URL=postgres://localhost\nDOTTED_POOL_MAX=5\nUNKNOWN=1\n",
+        )?;
+
+        assert_eq!("postgres://localhost", config.dotted_url);
+        assert_eq!(5, config.dotted_pool.max);
+
+        Ok(())
+    }
+}
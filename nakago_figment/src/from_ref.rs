@@ -16,3 +16,68 @@ where
         input.clone()
     }
 }
+
+#[cfg(test)]
+pub(crate) mod test {
+    use nakago_derive::FromRef;
+
+    use super::*;
+
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
+    pub struct Auth {
+        pub url: String,
+    }
+
+    #[derive(Clone, Debug, Default, PartialEq, Eq, FromRef)]
+    pub struct Http {
+        pub port: u16,
+        pub auth: Auth,
+    }
+
+    #[derive(Clone, Debug, Default, PartialEq, Eq, FromRef)]
+    pub struct AppConfig {
+        #[from_ref(flatten(Auth))]
+        pub http: Http,
+
+        #[from_ref(skip)]
+        pub name: String,
+    }
+
+    #[derive(Clone, Debug, Default, PartialEq, Eq, FromRef)]
+    pub struct GenericConfig<T: Clone> {
+        pub http: Http,
+        pub extra: T,
+        pub items: Vec<T>,
+    }
+
+    #[test]
+    fn test_from_ref_flatten() {
+        let config = AppConfig {
+            http: Http {
+                port: 8080,
+                auth: Auth {
+                    url: "https://example.com".to_string(),
+                },
+            },
+            name: "test".to_string(),
+        };
+
+        assert_eq!(Http::from_ref(&config), config.http);
+        assert_eq!(Auth::from_ref(&config), config.http.auth);
+    }
+
+    #[test]
+    fn test_from_ref_generics() {
+        let config = GenericConfig {
+            http: Http {
+                port: 8080,
+                auth: Auth::default(),
+            },
+            extra: 42u32,
+            items: vec![1, 2, 3],
+        };
+
+        assert_eq!(Http::from_ref(&config), config.http);
+        assert_eq!(Vec::<u32>::from_ref(&config), config.items);
+    }
+}
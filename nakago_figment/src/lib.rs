@@ -7,13 +7,55 @@ pub mod loader;
 /// Config loader init helpers
 pub mod loaders;
 
+/// Command-line Config arguments
+pub mod args;
+
+/// Fallible and asynchronous Config loading
+pub mod async_loader;
+
+/// Pluggable Config file formats
+pub mod formats;
+
 /// The FromRef Utility
 pub mod from_ref;
 
 /// The Config trait
 pub mod config;
 
+/// Environment variable Config loading
+pub mod env;
+
+/// Named multi-instance Config sections
+pub mod named;
+
+/// Effective Config reports
+pub mod report;
+
+/// JSON Schema and sample config generation
+#[cfg(feature = "schema")]
+pub mod schema;
+
+/// Secret Config values
+pub mod secret;
+
+/// Secret file Config loading
+pub mod secrets;
+
+/// Config validation
+pub mod validate;
+
+/// Config hot-reloading
+pub mod watch;
+
+pub use args::Args;
+pub use async_loader::{AsyncLoader, AsyncLoaders, LoaderError};
 pub use config::Config;
+pub use formats::{FileFormat, Formats};
 pub use from_ref::FromRef;
 pub use loader::Loader;
 pub use loaders::{Init, Loaders};
+pub use named::Named;
+pub use report::Report;
+pub use secret::Secret;
+pub use validate::{ConfigError, Validate};
+pub use watch::{Watch, Watcher};
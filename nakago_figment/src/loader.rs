@@ -1,11 +1,30 @@
-use std::{any::Any, marker::PhantomData, path::PathBuf};
+use std::{
+    any::Any,
+    env, fs,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use figment::{
     providers::{Format, Json, Serialized, Toml, Yaml},
     Figment,
 };
 
-use crate::{loaders::Loaders, Config};
+use crate::{
+    args::Override,
+    async_loader::{self, AsyncLoaders, DEFAULT_TIMEOUT},
+    formats::{Formats, Unloadable},
+    loaders::Loaders,
+    validate::{ConfigError, Violations},
+    Config,
+};
+
+/// The environment variable used to select a profile, such as `test` or `ci`
+pub const PROFILE_VAR: &str = "NAKAGO_PROFILE";
+
+/// The file extensions that are supported without any additional Formats, in order
+const EXTENSIONS: [&str; 4] = ["toml", "yml", "yaml", "json"];
 
 /// A Loader uses hooks to augment the Config loaded for the application
 pub trait Loader: Any + Send + Sync {
@@ -16,6 +35,12 @@ pub trait Loader: Any + Send + Sync {
 /// An extensible Config loader based on Figment
 pub struct LoadAll<C: Config> {
     loaders: Loaders,
+    async_loaders: AsyncLoaders,
+    timeout: Duration,
+    formats: Formats,
+    overrides: Vec<Override>,
+    dir: PathBuf,
+    profile: Option<String>,
     _phantom: PhantomData<C>,
 }
 
@@ -24,32 +49,107 @@ impl<C: Config> LoadAll<C> {
     pub fn new(loaders: Loaders) -> Self {
         Self {
             loaders,
+            async_loaders: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+            formats: Vec::new(),
+            overrides: Vec::new(),
+            dir: PathBuf::from("."),
+            profile: None,
             _phantom: Default::default(),
         }
     }
 
-    /// Create a new Config by merging in various sources
+    /// Run the given AsyncLoaders after the Loaders, when loading with `load_async`
+    pub fn with_async_loaders(self, async_loaders: AsyncLoaders) -> Self {
+        Self {
+            async_loaders,
+            ..self
+        }
+    }
+
+    /// How long each AsyncLoader may run, which defaults to 10 seconds
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// Apply the given command-line overrides last, so that they take precedence over every other
+    /// source
+    pub fn with_overrides(self, overrides: Vec<Override>) -> Self {
+        Self { overrides, ..self }
+    }
+
+    /// Support additional file formats, which are merged after the built-in formats in each layer
+    pub fn with_formats(self, formats: Formats) -> Self {
+        Self { formats, ..self }
+    }
+
+    /// Search for the Config files in the given directory rather than the working directory
+    pub fn with_dir(self, dir: PathBuf) -> Self {
+        Self { dir, ..self }
+    }
+
+    /// Use the given profile rather than the value of the `NAKAGO_PROFILE` variable
+    pub fn with_profile(self, profile: impl Into<String>) -> Self {
+        Self {
+            profile: Some(profile.into()),
+            ..self
+        }
+    }
+
+    /// Create a new Config by merging in various sources. Each layer is loaded in toml, yml, yaml
+    /// and json formats, in this order:
+    ///
+    /// - `config.*`
+    /// - `config.{profile}.*`, if a profile is selected
+    /// - `config.local.*`
+    /// - `conf.d/*`, in lexical order
+    /// - the custom path, if provided
+    ///
+    /// The Loaders are applied after that, followed by the command-line overrides. A custom path
+    /// that doesn't exist or doesn't have a supported extension is reported as an error when the
+    /// Config is extracted.
     pub fn load(&self, custom_path: Option<PathBuf>) -> Figment {
-        let mut config = Figment::new()
-            // Load defaults
-            .merge(Serialized::defaults(C::default()))
-            // Load local overrides
-            .merge(Toml::file("config.toml"))
-            .merge(Yaml::file("config.yml"))
-            .merge(Yaml::file("config.yaml"))
-            .merge(Json::file("config.json"));
+        self.merge_overrides(self.load_sources(custom_path))
+    }
+
+    /// Load the Config like `load`, running the AsyncLoaders in order before the command-line
+    /// overrides. The first AsyncLoader that fails or times out is reported as a `LoaderError`.
+    pub async fn load_async(
+        &self,
+        custom_path: Option<PathBuf>,
+    ) -> Result<Figment, async_loader::LoaderError> {
+        self.load_async_from(self.load_sources(custom_path)).await
+    }
+
+    // Run the AsyncLoaders on sources that were already loaded, then apply the overrides
+    async fn load_async_from(
+        &self,
+        sources: Figment,
+    ) -> Result<Figment, async_loader::LoaderError> {
+        let figment = async_loader::run(&self.async_loaders, sources, self.timeout).await?;
+
+        Ok(self.merge_overrides(figment))
+    }
+
+    // The defaults, files and Loaders, without the command-line overrides. The files are read here.
+    pub(crate) fn load_sources(&self, custom_path: Option<PathBuf>) -> Figment {
+        // Load defaults
+        let mut config = Figment::new().merge(Serialized::defaults(C::default()));
+
+        // Load the layered overrides
+        for path in self.layers() {
+            config = self.merge_file(config, &path);
+        }
 
         // Load the custom config file if provided
         if let Some(path) = custom_path {
-            if let Some(path_str) = path.to_str() {
-                if path_str.ends_with(".toml") {
-                    config = config.merge(Toml::file(path_str));
-                } else if path_str.ends_with(".yml") || path_str.ends_with(".yaml") {
-                    config = config.merge(Yaml::file(path_str));
-                } else if path_str.ends_with(".json") {
-                    config = config.merge(Json::file(path_str));
-                }
-            }
+            config = if !path.is_file() {
+                config.merge(Unloadable::new(path, "does not exist"))
+            } else if !self.is_supported(&path) {
+                config.merge(Unloadable::new(path, "has an unsupported extension"))
+            } else {
+                self.merge_file(config, &path)
+            };
         }
 
         // Apply individual loaders to transform the Figment provider
@@ -59,10 +159,146 @@ impl<C: Config> LoadAll<C> {
 
         config
     }
+
+    fn merge_overrides(&self, figment: Figment) -> Figment {
+        self.overrides
+            .iter()
+            .fold(figment, |figment, item| figment.merge(item.clone()))
+    }
+
+    /// The selected profile, if any
+    pub fn profile(&self) -> Option<String> {
+        self.profile
+            .clone()
+            .or_else(|| env::var(PROFILE_VAR).ok())
+            .filter(|profile| !profile.is_empty() && profile != "local")
+    }
+
+    /// Load and extract the Config, then run its validation hook
+    pub fn extract(&self, custom_path: Option<PathBuf>) -> Result<(C, Figment), ConfigError> {
+        validate(self.load(custom_path))
+    }
+
+    /// Load the Config with `load_async`, then extract and validate it. The error is either a
+    /// `LoaderError` or a `ConfigError`.
+    pub async fn extract_async(
+        &self,
+        custom_path: Option<PathBuf>,
+    ) -> anyhow::Result<(C, Figment)> {
+        self.extract_sources_async(self.load_sources(custom_path))
+            .await
+    }
+
+    // Extract the Config like `extract_async`, from sources that were already loaded
+    pub(crate) async fn extract_sources_async(
+        &self,
+        sources: Figment,
+    ) -> anyhow::Result<(C, Figment)> {
+        let figment = self.load_async_from(sources).await?;
+
+        Ok(validate(figment)?)
+    }
+
+    /// The files that may be merged into the Config, whether or not they currently exist
+    pub fn paths(&self, custom_path: Option<PathBuf>) -> Vec<PathBuf> {
+        let mut paths = self.layers();
+
+        if let Some(path) = custom_path {
+            paths.push(path);
+        }
+
+        paths
+    }
+
+    // The layered files in the search directory, followed by the fragments in `conf.d`
+    fn layers(&self) -> Vec<PathBuf> {
+        let mut names = vec!["config".to_string()];
+        if let Some(profile) = self.profile() {
+            names.push(format!("config.{profile}"));
+        }
+        names.push("config.local".to_string());
+
+        let extensions = self.extensions();
+
+        let mut paths: Vec<PathBuf> = names
+            .iter()
+            .flat_map(|name| {
+                extensions
+                    .iter()
+                    .map(move |ext| self.dir.join(format!("{name}.{ext}")))
+            })
+            .collect();
+
+        if let Ok(entries) = fs::read_dir(self.dir.join("conf.d")) {
+            let mut fragments: Vec<PathBuf> = entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| self.is_supported(path))
+                .collect();
+
+            fragments.sort();
+            paths.extend(fragments);
+        }
+
+        paths
+    }
+
+    // The built-in extensions followed by those of the additional Formats
+    fn extensions(&self) -> Vec<&'static str> {
+        let mut extensions = EXTENSIONS.to_vec();
+
+        for format in &self.formats {
+            extensions.extend(format.extensions());
+        }
+
+        extensions
+    }
+
+    fn is_supported(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| self.extensions().contains(&ext))
+    }
+
+    // Merge a Config file using the format that matches its extension
+    fn merge_file(&self, config: Figment, path: &Path) -> Figment {
+        let Some(ext) = path.extension().and_then(|ext| ext.to_str()) else {
+            return config;
+        };
+
+        match ext {
+            "toml" => config.merge(Toml::file(path)),
+            "yml" | "yaml" => config.merge(Yaml::file(path)),
+            "json" => config.merge(Json::file(path)),
+            _ => match self
+                .formats
+                .iter()
+                .find(|format| format.extensions().contains(&ext))
+            {
+                Some(format) => format.merge(config, path),
+                None => config,
+            },
+        }
+    }
+}
+
+// Extract the Config, then run its validation hook
+fn validate<C: Config>(figment: Figment) -> Result<(C, Figment), ConfigError> {
+    let config: C = figment.extract().map_err(ConfigError::from_figment)?;
+
+    let mut violations = Violations::default();
+    config.validate(&mut violations);
+
+    if !violations.is_empty() {
+        return Err(ConfigError::from_violations(violations, &figment));
+    }
+
+    Ok((config, figment))
 }
 
 #[cfg(test)]
 pub(crate) mod test {
+    use std::sync::Arc;
+
     use anyhow::Result;
     use serde::{Deserialize, Serialize};
 
@@ -93,4 +329,175 @@ pub(crate) mod test {
 
         Ok(())
     }
+
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+    pub struct Layered {
+        pub base: String,
+        pub profile: String,
+        pub local: String,
+        pub fragment: String,
+        pub custom: String,
+    }
+
+    impl crate::Config for Layered {}
+
+    // Write a set of layered files to a new temporary directory
+    fn layered_dir() -> Result<PathBuf> {
+        let dir = env::temp_dir().join(format!("nakago-loader-{}", ulid::Ulid::new()));
+        fs::create_dir_all(dir.join("conf.d"))?;
+
+        fs::write(
+            dir.join("config.toml"),
+            "base = \"config\"\nprofile = \"config\"\nlocal = \"config\"\n",
+        )?;
+        fs::write(
+            dir.join("config.test.yml"),
+            "profile: test\nlocal: test\nfragment: test\n",
+        )?;
+        fs::write(dir.join("config.local.json"), r#"{"local": "local"}"#)?;
+        fs::write(dir.join("conf.d/20-b.toml"), "fragment = \"20-b\"\n")?;
+        fs::write(dir.join("conf.d/10-a.toml"), "fragment = \"10-a\"\n")?;
+        fs::write(dir.join("conf.d/README.md"), "ignored")?;
+
+        Ok(dir)
+    }
+
+    #[tokio::test]
+    async fn test_load_all_profile_layers() -> Result<()> {
+        let dir = layered_dir()?;
+
+        let loader = LoadAll::<Layered>::new(vec![])
+            .with_dir(dir.clone())
+            .with_profile("test");
+
+        let config: Layered = loader.load(None).extract()?;
+
+        assert_eq!("config", config.base);
+        assert_eq!("test", config.profile);
+        assert_eq!("local", config.local);
+        assert_eq!("20-b", config.fragment);
+
+        // The custom path is merged last
+        let custom = dir.join("custom.toml");
+        fs::write(&custom, "custom = \"custom\"\nlocal = \"custom\"\n")?;
+
+        let config: Layered = loader.load(Some(custom)).extract()?;
+
+        assert_eq!("custom", config.custom);
+        assert_eq!("custom", config.local);
+
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_all_without_profile() -> Result<()> {
+        let dir = layered_dir()?;
+
+        let loader = LoadAll::<Layered>::new(vec![])
+            .with_dir(dir.clone())
+            .with_profile("");
+
+        assert!(loader.profile().is_none());
+
+        let config: Layered = loader.load(None).extract()?;
+
+        assert_eq!("config", config.profile);
+        assert_eq!("local", config.local);
+        assert_eq!("20-b", config.fragment);
+
+        let paths = loader.paths(None);
+        assert_eq!(dir.join("config.toml"), paths[0]);
+        assert_eq!(dir.join("config.local.toml"), paths[4]);
+        assert_eq!(dir.join("conf.d/10-a.toml"), paths[8]);
+        assert_eq!(10, paths.len());
+
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_all_custom_path_errors() -> Result<()> {
+        let dir = layered_dir()?;
+        let loader = LoadAll::<Layered>::new(vec![]).with_dir(dir.clone());
+
+        // A missing custom path is an error rather than silently ignored
+        let Err(err) = loader.extract(Some(dir.join("missing.toml"))) else {
+            panic!("did not return Err as expected")
+        };
+        assert!(err.to_string().contains("missing.toml does not exist"));
+
+        // An unknown extension is an error as well
+        let unknown = dir.join("custom.tml");
+        fs::write(&unknown, "custom = \"custom\"\n")?;
+
+        let Err(err) = loader.extract(Some(unknown)) else {
+            panic!("did not return Err as expected")
+        };
+        assert!(err
+            .to_string()
+            .contains("custom.tml has an unsupported extension"));
+
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    // A minimal format with one `key=value` pair per line
+    struct Pairs {}
+
+    impl crate::FileFormat for Pairs {
+        fn extensions(&self) -> Vec<&'static str> {
+            vec!["pairs"]
+        }
+
+        fn merge(&self, figment: Figment, path: &Path) -> Figment {
+            figment.merge(crate::formats::Parsed::new(
+                "pairs file",
+                path.to_path_buf(),
+                |contents| {
+                    let mut dict = figment::value::Dict::new();
+
+                    for line in contents.lines() {
+                        let (key, value) = line.split_once('=').ok_or("expected key=value")?;
+                        dict.insert(key.to_string(), value.into());
+                    }
+
+                    Ok(dict)
+                },
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_load_all_custom_format() -> Result<()> {
+        let dir = layered_dir()?;
+        fs::write(dir.join("conf.d/30-c.pairs"), "fragment=30-c\n")?;
+
+        let loader = LoadAll::<Layered>::new(vec![])
+            .with_dir(dir.clone())
+            .with_formats(vec![Arc::new(Pairs {})]);
+
+        let custom = dir.join("custom.pairs");
+        fs::write(&custom, "custom=pairs\n")?;
+
+        let (config, _) = loader.extract(Some(custom.clone()))?;
+
+        assert_eq!("30-c", config.fragment);
+        assert_eq!("pairs", config.custom);
+
+        // Parse errors are reported with the file
+        fs::write(&custom, "custom\n")?;
+
+        let Err(err) = loader.extract(Some(custom)) else {
+            panic!("did not return Err as expected")
+        };
+        assert!(err.to_string().contains("expected key=value"));
+
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
 }
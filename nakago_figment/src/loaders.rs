@@ -1,9 +1,16 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{path::PathBuf, sync::Arc, time::Duration};
 
 use derive_new::new;
 use nakago::{Error, Inject, Result, Tag};
 
-use crate::{loader::LoadAll, Config, Loader};
+use crate::{
+    args::{Args, Override},
+    async_loader::AsyncLoaders,
+    formats::Formats,
+    loader::LoadAll,
+    report::Report,
+    Config, Loader,
+};
 
 /// Loaders is a convenience type for a collection of Loader instances
 pub type Loaders = Vec<Arc<dyn Loader>>;
@@ -57,6 +64,18 @@ pub struct Init<C: Config> {
     custom_path: Option<PathBuf>,
     loaders_tag: Option<&'static Tag<Loaders>>,
     config_tag: Option<&'static Tag<C>>,
+    #[new(default)]
+    dir: Option<PathBuf>,
+    #[new(default)]
+    profile: Option<String>,
+    #[new(default)]
+    formats_tag: Option<&'static Tag<Formats>>,
+    #[new(default)]
+    async_loaders_tag: Option<&'static Tag<AsyncLoaders>>,
+    #[new(default)]
+    timeout: Option<Duration>,
+    #[new(default)]
+    overrides: Vec<Override>,
 }
 
 impl<C: Config> Init<C> {
@@ -76,6 +95,32 @@ impl<C: Config> Init<C> {
         }
     }
 
+    /// Use the custom path and the `--set` overrides from the command-line arguments. The overrides
+    /// take precedence over every other source.
+    pub fn with_args(self, args: &Args<C>) -> Self {
+        Self {
+            custom_path: args.config.clone().or(self.custom_path),
+            overrides: args.overrides.clone(),
+            ..self
+        }
+    }
+
+    /// Search for the Config files in the given directory rather than the working directory
+    pub fn with_dir(self, dir: PathBuf) -> Self {
+        Self {
+            dir: Some(dir),
+            ..self
+        }
+    }
+
+    /// Use the given profile rather than the value of the `NAKAGO_PROFILE` variable
+    pub fn with_profile(self, profile: impl Into<String>) -> Self {
+        Self {
+            profile: Some(profile.into()),
+            ..self
+        }
+    }
+
     /// Use a Config Tag when injecting the loaded Config
     pub fn with_config_tag(self, tag: &'static Tag<C>) -> Self {
         Self {
@@ -92,7 +137,33 @@ impl<C: Config> Init<C> {
         }
     }
 
-    /// Initialize the Config
+    /// Use a Formats Tag when retrieving the additional file Formats
+    pub fn with_formats_tag(self, tag: &'static Tag<Formats>) -> Self {
+        Self {
+            formats_tag: Some(tag),
+            ..self
+        }
+    }
+
+    /// Use an AsyncLoaders Tag when retrieving the current AsyncLoaders
+    pub fn with_async_loaders_tag(self, tag: &'static Tag<AsyncLoaders>) -> Self {
+        Self {
+            async_loaders_tag: Some(tag),
+            ..self
+        }
+    }
+
+    /// How long each AsyncLoader may run, which defaults to 10 seconds
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Initialize the Config, returning a `ConfigError` with every invalid or missing key if the
+    /// Config can't be extracted or doesn't pass validation, or a `LoaderError` naming the
+    /// AsyncLoader that failed. A `Report` of the effective Config is injected alongside it.
     pub async fn init(&self, i: &Inject) -> Result<()> {
         let loaders_result = match self.loaders_tag {
             Some(tag) => i.get_tag(tag).await,
@@ -100,12 +171,43 @@ impl<C: Config> Init<C> {
         };
 
         let loaders = loaders_result.unwrap_or_default().to_vec();
-        let loader = LoadAll::<C>::new(loaders);
 
-        let config = loader
-            .load(self.custom_path.clone())
-            .extract()
-            .map_err(|e| Error::Any(Arc::new(e.into())))?;
+        let formats_result = match self.formats_tag {
+            Some(tag) => i.get_tag(tag).await,
+            None => i.get::<Formats>().await,
+        };
+
+        let formats = formats_result.unwrap_or_default().to_vec();
+
+        let async_loaders_result = match self.async_loaders_tag {
+            Some(tag) => i.get_tag(tag).await,
+            None => i.get::<AsyncLoaders>().await,
+        };
+
+        let async_loaders = async_loaders_result.unwrap_or_default().to_vec();
+
+        let mut loader = LoadAll::<C>::new(loaders)
+            .with_formats(formats)
+            .with_async_loaders(async_loaders)
+            .with_overrides(self.overrides.clone());
+        if let Some(dir) = &self.dir {
+            loader = loader.with_dir(dir.clone());
+        }
+        if let Some(profile) = &self.profile {
+            loader = loader.with_profile(profile.clone());
+        }
+        if let Some(timeout) = self.timeout {
+            loader = loader.with_timeout(timeout);
+        }
+
+        let (config, figment) = loader
+            .extract_async(self.custom_path.clone())
+            .await
+            .map_err(|e| Error::Any(Arc::new(e)))?;
+
+        let _ = i
+            .override_type::<Report>(Report::new(&config, &figment))
+            .await?;
 
         if let Some(tag) = self.config_tag {
             i.inject_tag(tag, config).await?;
@@ -122,6 +224,8 @@ pub(crate) mod test {
     use std::sync::Arc;
 
     use figment::Figment;
+    use nakago_derive::{Config, Inject, Validate};
+    use serde::{Deserialize, Serialize};
 
     use crate::{loader::test::Config, Loader};
 
@@ -130,9 +234,46 @@ pub(crate) mod test {
     /// A Tag for Config loaders
     pub const LOADERS: Tag<Vec<Arc<dyn Loader>>> = Tag::new("config::Loaders");
 
+    /// A Tag for the loaded Config
+    pub const CONFIG: Tag<Config> = Tag::new("config::Config");
+
     #[derive(Default, Debug, PartialEq, Eq)]
     pub struct TestLoader {}
 
+    fn temp_path(ext: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("nakago-init-{}.{ext}", ulid::Ulid::new()))
+    }
+
+    #[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+    pub struct Auth {
+        #[validate(url)]
+        pub url: String,
+    }
+
+    impl Default for Auth {
+        fn default() -> Self {
+            Self {
+                url: "https://".to_string(),
+            }
+        }
+    }
+
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, Config, Validate)]
+    #[config(validate)]
+    pub struct ValidatedConfig {
+        #[validate(nested)]
+        pub auth: Auth,
+    }
+
+    #[derive(Inject)]
+    pub struct ConfiguredService {
+        #[inject(config)]
+        config: Config,
+
+        #[inject(optional)]
+        loaders: Option<Arc<Loaders>>,
+    }
+
     impl Loader for TestLoader {
         fn load(&self, figment: Figment) -> Figment {
             figment
@@ -210,7 +351,7 @@ pub(crate) mod test {
     }
 
     #[tokio::test]
-    async fn test_init_success() -> Result<()> {
+    async fn test_init_success() -> anyhow::Result<()> {
         // First test with no varations
         let i = Inject::default();
 
@@ -223,11 +364,14 @@ pub(crate) mod test {
 
         // Now test with custom path
         let i = Inject::default();
+        let path = temp_path("toml");
+        std::fs::write(&path, "")?;
 
-        let hook = Init::<Config>::default().with_path("TEST_PATH".into());
+        let hook = Init::<Config>::default().with_path(path.clone());
         assert!(hook.custom_path.is_some());
 
         hook.init(&i).await?;
+        std::fs::remove_file(path)?;
 
         // Finally, test with a loader tag
         let i = Inject::default();
@@ -242,14 +386,97 @@ pub(crate) mod test {
     }
 
     #[tokio::test]
-    async fn test_init_custom_path() -> Result<()> {
+    async fn test_init_custom_path() -> anyhow::Result<()> {
         let i = Inject::default();
 
-        let custom_path = PathBuf::from("config.toml");
+        let custom_path = temp_path("toml");
+        std::fs::write(&custom_path, "")?;
 
-        let hook = Init::<Config>::new(Some(custom_path), None, None);
+        let hook = Init::<Config>::new(Some(custom_path.clone()), None, None);
 
         hook.init(&i).await?;
+        std::fs::remove_file(custom_path)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_init_custom_path_missing() -> Result<()> {
+        let i = Inject::default();
+
+        let result = Init::<Config>::default()
+            .with_path(temp_path("toml"))
+            .init(&i)
+            .await;
+
+        let Err(Error::Any(err)) = result else {
+            panic!("did not return Err as expected")
+        };
+        assert!(err.to_string().contains("does not exist"));
+
+        // The Config is not injected
+        assert!(i.get_opt::<Config>().await?.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_derive_inject_config() -> Result<()> {
+        let i = Inject::default();
+
+        Init::<Config>::default().init(&i).await?;
+
+        i.provide::<ConfiguredService>(ConfiguredServiceProvider::<Config>::new())
+            .await?;
+
+        let service = i.get::<ConfiguredService>().await?;
+
+        assert_eq!(service.config, Config::default());
+        assert!(service.loaders.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_derive_inject_config_tag() -> Result<()> {
+        let i = Inject::default();
+
+        Init::<Config>::default()
+            .with_config_tag(&CONFIG)
+            .init(&i)
+            .await?;
+
+        i.provide::<ConfiguredService>(
+            ConfiguredServiceProvider::<Config>::new().with_config_tag(&CONFIG),
+        )
+        .await?;
+
+        let service = i.get::<ConfiguredService>().await?;
+
+        assert_eq!(service.config, Config::default());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_init_validation_error() -> Result<()> {
+        let i = Inject::default();
+
+        let result = Init::<ValidatedConfig>::default().init(&i).await;
+
+        let Err(Error::Any(err)) = result else {
+            panic!("did not return Err as expected")
+        };
+
+        let config_error = err
+            .downcast_ref::<crate::ConfigError>()
+            .expect("expected a ConfigError");
+
+        assert_eq!(1, config_error.errors.len());
+        assert_eq!("auth.url", config_error.errors[0].path);
+
+        // The invalid Config is not injected
+        assert!(i.get_opt::<ValidatedConfig>().await?.is_none());
 
         Ok(())
     }
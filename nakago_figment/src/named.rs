@@ -0,0 +1,283 @@
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::{btree_map, BTreeMap, HashMap},
+    sync::{Mutex, OnceLock, PoisonError},
+};
+
+use anyhow::anyhow;
+use nakago::{provider, to_provider_error, Inject, Tag};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    validate::{Validate, Violations},
+    Config, FromRef,
+};
+
+/// A map-shaped Config section with one entry per named instance, such as `[database.primary]`
+/// and `[database.analytics]`. Each instance is a complete section of the given type.
+///
+/// Instances are validated with a path that includes their name, and integrations look them up by
+/// name to register one provider per instance under the matching `named::tag`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Named<T>(BTreeMap<String, T>);
+
+impl<T> Default for Named<T> {
+    fn default() -> Self {
+        Self(BTreeMap::new())
+    }
+}
+
+impl<T> Named<T> {
+    /// Add an instance, replacing any existing instance with the same name
+    pub fn with(mut self, name: &str, instance: T) -> Self {
+        self.0.insert(name.to_string(), instance);
+        self
+    }
+
+    /// Get an instance by name
+    pub fn get(&self, name: &str) -> Option<&T> {
+        self.0.get(name)
+    }
+
+    /// The instance names, in sorted order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    /// Iterate over the instances by name, in sorted order
+    pub fn iter(&self) -> btree_map::Iter<'_, String, T> {
+        self.0.iter()
+    }
+
+    /// The number of instances
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether there are no instances
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<T> FromIterator<(String, T)> for Named<T> {
+    fn from_iter<I: IntoIterator<Item = (String, T)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Named<T> {
+    type Item = (&'a String, &'a T);
+    type IntoIter = btree_map::Iter<'a, String, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<T: Validate> Validate for Named<T> {
+    fn validate(&self, violations: &mut Violations) {
+        for (name, instance) in &self.0 {
+            violations.nested(name, |violations| instance.validate(violations));
+        }
+    }
+}
+
+#[cfg(feature = "schema")]
+impl<T: schemars::JsonSchema> schemars::JsonSchema for Named<T> {
+    fn schema_name() -> String {
+        format!("Named_{}", T::schema_name())
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        BTreeMap::<String, T>::json_schema(gen)
+    }
+}
+
+/// Get the named instance from the map-shaped section of the Config in the container, using the
+/// Config Tag if one is given. Integrations use this in the Provider for each named instance, and
+/// fail if there is no instance with the given name.
+pub async fn instance<C, T>(
+    i: &Inject,
+    config_tag: Option<&'static Tag<C>>,
+    name: &str,
+) -> provider::Result<T>
+where
+    C: Config,
+    T: Clone,
+    Named<T>: FromRef<C>,
+{
+    let config = match config_tag {
+        Some(tag) => i.get_tag(tag).await?,
+        None => i.get::<C>().await?,
+    };
+
+    Named::<T>::from_ref(&*config)
+        .get(name)
+        .cloned()
+        .ok_or_else(|| {
+            to_provider_error(anyhow!("no {} instance named {name:?}", type_name::<T>()))
+        })
+}
+
+type Tags = HashMap<(TypeId, String), &'static (dyn Any + Send + Sync)>;
+
+/// The Tag for the named instance of a Dependency, such as the `DatabaseConnection` for the
+/// `primary` database. The same Tag is returned for each call with the same type and name.
+pub fn tag<T: Any + Send + Sync>(name: &str) -> &'static Tag<T> {
+    static TAGS: OnceLock<Mutex<Tags>> = OnceLock::new();
+
+    let mut tags = TAGS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    let tag = tags
+        .entry((TypeId::of::<T>(), name.to_string()))
+        .or_insert_with(|| {
+            // Tags are static, so each one is created once and kept for the life of the process
+            let id: &'static str = Box::leak(format!("{}({name})", type_name::<T>()).into());
+
+            Box::leak(Box::new(Tag::<T>::new(id)))
+        });
+
+    tag.downcast_ref::<Tag<T>>()
+        .expect("named Tags are keyed by their type")
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use figment::{
+        providers::{Format, Serialized, Toml},
+        Figment,
+    };
+    use nakago::Inject;
+    use nakago_derive::{Config, Validate};
+    use pretty_assertions::assert_eq;
+
+    use crate::loader::LoadAll;
+
+    use super::*;
+
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Validate)]
+    pub struct Database {
+        #[validate(non_empty)]
+        pub url: String,
+    }
+
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Config, Validate)]
+    #[config(validate)]
+    pub struct Databases {
+        #[validate(nested)]
+        pub database: Named<Database>,
+    }
+
+    #[test]
+    fn test_named_sections() -> anyhow::Result<()> {
+        let figment =
+            Figment::from(Serialized::defaults(Databases::default())).merge(Toml::string(
+                r#"
+                [database.primary]
+                url = "postgres://primary"
+
+                [database.analytics]
+                url = "postgres://analytics"
+                "#,
+            ));
+
+        let config: Databases = figment.extract()?;
+
+        assert_eq!(
+            vec!["analytics", "primary"],
+            config.database.names().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            "postgres://primary",
+            config.database.get("primary").unwrap().url
+        );
+
+        // FromRef is generated for the map-shaped section
+        let named = <Named<Database> as crate::FromRef<Databases>>::from_ref(&config);
+        assert_eq!(2, named.len());
+
+        // Overrides can target instances that aren't in the defaults
+        let args = crate::Args::<Databases>::from_args(["--set", "database.replica.url=x"])?;
+        assert_eq!("database.replica.url", args.overrides[0].key);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_named_validation() -> anyhow::Result<()> {
+        let dir = std::env::temp_dir().join(format!("nakago-named-{}", ulid::Ulid::new()));
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(
+            dir.join("config.toml"),
+            "[database.primary]\nurl = \"postgres://primary\"\n[database.analytics]\nurl = \"\"\n",
+        )?;
+
+        let result = LoadAll::<Databases>::new(vec![])
+            .with_dir(dir.clone())
+            .extract(None);
+
+        let Err(err) = result else {
+            panic!("did not return Err as expected")
+        };
+        assert_eq!("database.analytics.url", err.errors[0].path);
+
+        std::fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_named_instance() -> anyhow::Result<()> {
+        let i = Inject::default();
+
+        let database = Named::default()
+            .with(
+                "primary",
+                Database {
+                    url: "postgres://primary".to_string(),
+                },
+            )
+            .with(
+                "analytics",
+                Database {
+                    url: "postgres://analytics".to_string(),
+                },
+            );
+
+        i.inject(Databases { database }).await?;
+
+        let primary: Database = instance::<Databases, _>(&i, None, "primary").await?;
+        assert_eq!("postgres://primary", primary.url);
+
+        let analytics: Database = instance::<Databases, _>(&i, None, "analytics").await?;
+        assert_eq!("postgres://analytics", analytics.url);
+
+        let result = instance::<Databases, Database>(&i, None, "missing").await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_named_tags() -> anyhow::Result<()> {
+        let i = Inject::default();
+
+        let primary = tag::<String>("primary");
+        assert!(std::ptr::eq(primary, tag::<String>("primary")));
+        assert_ne!(&**primary, &**tag::<String>("analytics"));
+
+        i.inject_tag(primary, "primary".to_string()).await?;
+        i.inject_tag(tag::<String>("analytics"), "analytics".to_string())
+            .await?;
+
+        assert_eq!("primary", *i.get_tag(tag::<String>("primary")).await?);
+        assert_eq!("analytics", *i.get_tag(tag::<String>("analytics")).await?);
+
+        Ok(())
+    }
+}
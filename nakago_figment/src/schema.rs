@@ -0,0 +1,393 @@
+use std::{fmt::Write as _, fs, io, path::Path};
+
+use schemars::{
+    gen::SchemaSettings,
+    schema::{InstanceType, RootSchema, Schema, SchemaObject, SingleOrVec},
+    JsonSchema,
+};
+use serde_json::Value;
+
+use crate::{secret::redacted, Config};
+
+/// Generate a JSON Schema for the Config, including the doc comment of each field as its
+/// description and the value from the Config's `Default` implementation as its default.
+///
+/// Point an editor at the schema to autocomplete `config.toml`, or check config files against it
+/// in CI.
+pub fn schema<C: Config + JsonSchema>() -> RootSchema {
+    let mut root = SchemaSettings::draft07()
+        .into_generator()
+        .into_root_schema_for::<C>();
+
+    let defaults = redacted(|| serde_json::to_value(C::default())).unwrap_or(Value::Null);
+
+    let mut definitions = root.definitions.clone();
+    apply_defaults(&mut root.schema, &mut definitions, &defaults);
+    root.definitions = definitions;
+
+    root
+}
+
+/// Generate a JSON Schema for the Config as a pretty-printed JSON document
+pub fn schema_json<C: Config + JsonSchema>() -> String {
+    serde_json::to_string_pretty(&schema::<C>()).unwrap_or_default()
+}
+
+/// Generate a fully commented sample `config.toml`, listing every key with its description and
+/// default value. Keys without a default are commented out.
+pub fn example<C: Config + JsonSchema>() -> String {
+    let root = schema::<C>();
+    let defaults = redacted(|| serde_json::to_value(C::default())).unwrap_or(Value::Null);
+
+    let mut out = String::new();
+
+    if let Some(description) = description(&root.schema) {
+        comment(&mut out, &description);
+        out.push('\n');
+    }
+
+    write_table(&mut out, &root, &root.schema, &defaults, None);
+
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+
+    out
+}
+
+/// Write a fully commented sample config file, such as `config.example.toml`
+pub fn write_example<C: Config + JsonSchema>(path: &Path) -> io::Result<()> {
+    fs::write(path, example::<C>())
+}
+
+// Set the default of each property from the tree of default values, following references into the
+// definitions. Definitions that are shared by several fields keep the first default they are given.
+fn apply_defaults(
+    schema: &mut SchemaObject,
+    definitions: &mut schemars::Map<String, Schema>,
+    defaults: &Value,
+) {
+    let Some(object) = schema.object.as_mut() else {
+        return;
+    };
+
+    for (name, property) in object.properties.iter_mut() {
+        let Some(default) = defaults.get(name) else {
+            continue;
+        };
+
+        let Schema::Object(property) = property else {
+            continue;
+        };
+
+        // Siblings of a $ref are ignored in draft 7, so the reference is wrapped first
+        if let Some(reference) = property.reference.take() {
+            property.subschemas().all_of = Some(vec![Schema::new_ref(reference)]);
+        }
+
+        property.metadata().default = Some(default.clone());
+
+        if let Some(name) = reference_name(property) {
+            if let Some(Schema::Object(mut definition)) = definitions.remove(&name) {
+                apply_defaults(&mut definition, definitions, default);
+                definitions
+                    .entry(name)
+                    .or_insert(Schema::Object(definition));
+            }
+        } else {
+            apply_defaults(property, definitions, default);
+        }
+    }
+}
+
+// The definition name that a property refers to, directly or through a single `allOf`
+fn reference_name(schema: &SchemaObject) -> Option<String> {
+    let reference = match &schema.reference {
+        Some(reference) => reference,
+        None => match schema.subschemas.as_ref()?.all_of.as_deref()? {
+            [Schema::Object(inner)] => inner.reference.as_ref()?,
+            _ => return None,
+        },
+    };
+
+    reference
+        .strip_prefix("#/definitions/")
+        .map(ToString::to_string)
+}
+
+// Resolve a property to the schema that describes its shape, following references and skipping
+// the null branch of an optional value
+fn resolve<'a>(root: &'a RootSchema, schema: &'a SchemaObject) -> &'a SchemaObject {
+    if let Some(name) = reference_name(schema) {
+        if let Some(Schema::Object(definition)) = root.definitions.get(&name) {
+            return resolve(root, definition);
+        }
+    }
+
+    let branches = schema.subschemas.as_ref().and_then(|subschemas| {
+        subschemas
+            .any_of
+            .as_deref()
+            .or(subschemas.one_of.as_deref())
+    });
+
+    if let Some(branches) = branches {
+        let inner = branches.iter().find_map(|branch| match branch {
+            Schema::Object(inner) if !is_null(inner) => Some(inner),
+            _ => None,
+        });
+
+        if let Some(inner) = inner {
+            return resolve(root, inner);
+        }
+    }
+
+    schema
+}
+
+fn is_null(schema: &SchemaObject) -> bool {
+    matches!(
+        &schema.instance_type,
+        Some(SingleOrVec::Single(instance_type)) if **instance_type == InstanceType::Null
+    )
+}
+
+fn is_table(schema: &SchemaObject) -> bool {
+    schema
+        .object
+        .as_ref()
+        .is_some_and(|object| !object.properties.is_empty())
+}
+
+fn description(schema: &SchemaObject) -> Option<String> {
+    schema.metadata.as_ref()?.description.clone()
+}
+
+fn comment(out: &mut String, text: &str) {
+    for line in text.lines() {
+        let _ = writeln!(out, "# {line}");
+    }
+}
+
+// Write the scalar keys of a table, followed by each nested table under its own header
+fn write_table(
+    out: &mut String,
+    root: &RootSchema,
+    schema: &SchemaObject,
+    defaults: &Value,
+    path: Option<&str>,
+) {
+    let schema = resolve(root, schema);
+    let Some(object) = &schema.object else {
+        return;
+    };
+
+    let mut tables = Vec::new();
+
+    for (name, property) in &object.properties {
+        let Schema::Object(property) = property else {
+            continue;
+        };
+
+        let resolved = resolve(root, property);
+        let default = defaults.get(name).unwrap_or(&Value::Null);
+
+        if is_table(resolved) && !default.is_null() {
+            tables.push((name, property, default));
+            continue;
+        }
+
+        if let Some(description) = description(property).or_else(|| description(resolved)) {
+            comment(out, &description);
+        }
+
+        match toml_value(default) {
+            Some(value) => {
+                let _ = writeln!(out, "{name} = {value}");
+            }
+            None => {
+                let _ = writeln!(out, "# {name} = {}", placeholder(resolved));
+            }
+        }
+
+        out.push('\n');
+    }
+
+    for (name, property, default) in tables {
+        let key = match path {
+            Some(path) => format!("{path}.{name}"),
+            None => name.clone(),
+        };
+
+        if let Some(description) =
+            description(property).or_else(|| description(resolve(root, property)))
+        {
+            comment(out, &description);
+        }
+
+        let _ = writeln!(out, "[{key}]");
+
+        write_table(out, root, property, default, Some(&key));
+    }
+}
+
+// Format a default value inline, or None if there is no default
+fn toml_value(value: &Value) -> Option<String> {
+    if value.is_null() {
+        return None;
+    }
+
+    toml::Value::try_from(value)
+        .ok()
+        .map(|value| value.to_string())
+}
+
+// An example value for a key without a default, based on its type
+fn placeholder(schema: &SchemaObject) -> &'static str {
+    let instance_type = match &schema.instance_type {
+        Some(SingleOrVec::Single(instance_type)) => Some(**instance_type),
+        Some(SingleOrVec::Vec(types)) => types
+            .iter()
+            .copied()
+            .find(|instance_type| *instance_type != InstanceType::Null),
+        None => None,
+    };
+
+    match instance_type {
+        Some(InstanceType::String) => "\"\"",
+        Some(InstanceType::Integer) => "0",
+        Some(InstanceType::Number) => "0.0",
+        Some(InstanceType::Boolean) => "false",
+        Some(InstanceType::Array) => "[]",
+        _ => "{}",
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use nakago_derive::Config;
+    use pretty_assertions::assert_eq;
+    use serde::{Deserialize, Serialize};
+
+    use crate::Secret;
+
+    use super::*;
+
+    /// Client credentials
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+    pub struct Client {
+        /// The client id
+        pub id: Option<String>,
+
+        /// The client secret
+        pub secret: Option<Secret<String>>,
+    }
+
+    /// Database connection pool
+    #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+    pub struct Pool {
+        /// The maximum number of connections
+        pub max: u32,
+    }
+
+    impl Default for Pool {
+        fn default() -> Self {
+            Self { max: 10 }
+        }
+    }
+
+    /// The Database config
+    #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+    pub struct Database {
+        /// The database url
+        pub url: String,
+
+        /// The connection pool
+        pub pool: Pool,
+    }
+
+    impl Default for Database {
+        fn default() -> Self {
+            Self {
+                url: "postgres://localhost/app".to_string(),
+                pool: Pool::default(),
+            }
+        }
+    }
+
+    /// The application config
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema, Config)]
+    pub struct AppConfig {
+        /// Enable debug logging
+        pub debug: bool,
+
+        /// The auth client
+        pub client: Client,
+
+        /// The primary database
+        pub database: Database,
+    }
+
+    #[test]
+    fn test_schema_docs_and_defaults() -> anyhow::Result<()> {
+        let json: Value = serde_json::from_str(&schema_json::<AppConfig>())?;
+
+        assert_eq!("The application config", json["description"]);
+        assert_eq!(
+            "Enable debug logging",
+            json["properties"]["debug"]["description"]
+        );
+        assert_eq!(false, json["properties"]["debug"]["default"]);
+
+        let database = &json["properties"]["database"];
+        assert_eq!("The primary database", database["description"]);
+        assert_eq!("postgres://localhost/app", database["default"]["url"]);
+        assert_eq!(
+            "#/definitions/Database",
+            database["allOf"][0]["$ref"].as_str().unwrap_or_default()
+        );
+
+        let pool = &json["definitions"]["Pool"]["properties"]["max"];
+        assert_eq!("The maximum number of connections", pool["description"]);
+        assert_eq!(10, pool["default"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_example_config() -> anyhow::Result<()> {
+        let example = example::<AppConfig>();
+
+        assert_eq!(
+            r#"# The application config
+
+# Enable debug logging
+debug = false
+
+# The auth client
+[client]
+# The client id
+# id = ""
+
+# The client secret
+# secret = ""
+
+# The primary database
+[database]
+# The database url
+url = "postgres://localhost/app"
+
+# The connection pool
+[database.pool]
+# The maximum number of connections
+max = 10
+"#,
+            example
+        );
+
+        // The example loads as the default Config
+        let config: AppConfig = toml::from_str(&example)?;
+        assert_eq!(AppConfig::default(), config);
+
+        Ok(())
+    }
+}
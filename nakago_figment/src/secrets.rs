@@ -0,0 +1,413 @@
+use std::{
+    env, fs,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use figment::{
+    error::Kind,
+    util::nest,
+    value::{Map, UncasedStr, Value},
+    Figment, Metadata, Profile, Provider, Source,
+};
+use nakago::{Inject, Result, Tag};
+
+use crate::{
+    env::{file_target, find, map_key, parse_value},
+    loaders, Config, Loaders,
+};
+
+/// A Config Loader for secrets that are stored in files rather than plain environment variables.
+///
+/// Variables ending in `_FILE`, such as `DATABASE_URL_FILE=/run/secrets/db`, are read from the
+/// given file and mapped onto the Config like the variable without the suffix. If a secrets
+/// directory is given, each file in it is mapped the same way using its name, such as
+/// `database_url`, or used as a dotted key if its name contains a `.`, such as
+/// `auth.client.secret`. Variables take precedence over the secrets directory.
+///
+/// Trailing newlines are trimmed, and the contents are used as a string unless the default value
+/// for the key is a number or a boolean, so a password like `true` or `0123` is never
+/// reinterpreted. Files that are world-writable are rejected, and with `strict` enabled, files that
+/// can be read by the group or other users are rejected as well.
+pub struct Loader<C: Config> {
+    dir: Option<PathBuf>,
+    strict: bool,
+    _phantom: PhantomData<C>,
+}
+
+impl<C: Config> Default for Loader<C> {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            strict: false,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<C: Config> Loader<C> {
+    /// Map each file in a docker or k8s style secrets directory, such as `/run/secrets`
+    pub fn with_dir(self, dir: PathBuf) -> Self {
+        Self {
+            dir: Some(dir),
+            ..self
+        }
+    }
+
+    /// Reject secret files that can be accessed by the group or other users
+    pub fn strict(self) -> Self {
+        Self {
+            strict: true,
+            ..self
+        }
+    }
+
+    // Merge the secret files from the secrets directory and the given environment variables
+    fn load_vars(
+        &self,
+        figment: Figment,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Figment {
+        let tree = serde_json::to_value(C::default()).unwrap_or(serde_json::Value::Null);
+        let options = C::env_options();
+
+        let mut secrets = Vec::new();
+
+        if let Some(dir) = &self.dir {
+            secrets.extend(dir_entries(dir).into_iter().filter_map(|(name, path)| {
+                let key = if name.contains('.') {
+                    find(&tree, &name).map(|_| name)
+                } else {
+                    map_key(&tree, &options, UncasedStr::new(&name)).map(|key| key.to_string())
+                };
+
+                key.map(|key| (key, path))
+            }));
+        }
+
+        secrets.extend(vars.into_iter().filter_map(|(name, path)| {
+            let key = file_target(&tree, &options, UncasedStr::new(&name))?;
+
+            Some((key.to_string(), PathBuf::from(path)))
+        }));
+
+        secrets.into_iter().fold(figment, |figment, (key, path)| {
+            let typed =
+                find(&tree, &key).is_some_and(|value| value.is_number() || value.is_boolean());
+
+            figment.merge(SecretFile {
+                key,
+                path,
+                strict: self.strict,
+                typed,
+            })
+        })
+    }
+}
+
+impl<C: Config> crate::Loader for Loader<C> {
+    fn load(&self, figment: Figment) -> Figment {
+        self.load_vars(figment, env::vars())
+    }
+}
+
+/// Add the secrets Loader for the given Config to the stack, optionally with a secrets directory.
+pub async fn add_loader<C: Config>(i: &Inject, dir: Option<PathBuf>) -> Result<()> {
+    loaders::Add::default()
+        .loaders(i, vec![Arc::new(maybe_with_dir::<C>(dir))])
+        .await
+}
+
+/// Add the secrets Loader for the given Config to the stack, optionally with a secrets directory.
+pub async fn add_loader_with_tag<C: Config>(
+    i: &Inject,
+    tag: &'static Tag<Loaders>,
+    dir: Option<PathBuf>,
+) -> Result<()> {
+    loaders::Add::default()
+        .with_tag(tag)
+        .loaders(i, vec![Arc::new(maybe_with_dir::<C>(dir))])
+        .await
+}
+
+fn maybe_with_dir<C: Config>(dir: Option<PathBuf>) -> Loader<C> {
+    match dir {
+        Some(dir) => Loader::default().with_dir(dir),
+        None => Loader::default(),
+    }
+}
+
+// The visible files in a secrets directory, skipping the hidden entries that k8s uses for its
+// atomic updates
+fn dir_entries(dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<(String, PathBuf)> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+
+            if name.starts_with('.') || !entry.path().is_file() {
+                return None;
+            }
+
+            Some((name, entry.path()))
+        })
+        .collect();
+
+    files.sort();
+    files
+}
+
+/// A single secret value read from a file
+struct SecretFile {
+    key: String,
+    path: PathBuf,
+    strict: bool,
+
+    // Whether the default value for the key is a number or a boolean, which the contents are
+    // parsed into
+    typed: bool,
+}
+
+impl SecretFile {
+    fn read(&self) -> std::result::Result<String, String> {
+        let metadata = fs::metadata(&self.path).map_err(|err| err.to_string())?;
+
+        check_permissions(&metadata, self.strict)?;
+
+        let contents = fs::read_to_string(&self.path).map_err(|err| err.to_string())?;
+
+        Ok(contents.trim_end_matches(['\n', '\r']).to_string())
+    }
+
+    fn error(&self, message: String) -> figment::Error {
+        let mut error = figment::Error::from(Kind::Message(format!(
+            "secret file {} for {}: {message}",
+            self.path.display(),
+            self.key
+        )));
+        error.path = self.key.split('.').map(String::from).collect();
+
+        error
+    }
+}
+
+impl Provider for SecretFile {
+    fn metadata(&self) -> Metadata {
+        Metadata::from("Secret file", Source::File(self.path.clone()))
+    }
+
+    fn data(&self) -> figment::Result<Map<Profile, figment::value::Dict>> {
+        let contents = self.read().map_err(|message| self.error(message))?;
+
+        let value = if self.typed {
+            parse_value(&contents)
+        } else {
+            Value::from(contents)
+        };
+
+        match nest(&self.key, value) {
+            Value::Dict(_, dict) => Ok(Profile::Default.collect(dict)),
+            _ => Err(self.error("the key is empty".to_string())),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn check_permissions(metadata: &fs::Metadata, strict: bool) -> std::result::Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = metadata.permissions().mode() & 0o777;
+
+    if mode & 0o002 != 0 {
+        return Err(format!("mode {mode:o} is world-writable"));
+    }
+
+    if strict && mode & 0o077 != 0 {
+        return Err(format!(
+            "mode {mode:o} can be accessed by the group or other users"
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_permissions(_metadata: &fs::Metadata, _strict: bool) -> std::result::Result<(), String> {
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    #[cfg(unix)]
+    use std::os::unix::fs::PermissionsExt;
+
+    use nakago_derive::Config;
+    use pretty_assertions::assert_eq;
+    use serde::{Deserialize, Serialize};
+
+    use crate::validate::ConfigError;
+
+    use super::*;
+
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+    pub struct Client {
+        pub id: Option<String>,
+        pub secret: Option<String>,
+    }
+
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+    pub struct Store {
+        pub url: String,
+        pub max: u32,
+        pub client: Client,
+    }
+
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Config)]
+    pub struct Secrets {
+        pub secrets_test: Store,
+    }
+
+    fn temp_dir() -> anyhow::Result<PathBuf> {
+        let dir = env::temp_dir().join(format!("nakago-secrets-{}", ulid::Ulid::new()));
+        fs::create_dir_all(&dir)?;
+
+        Ok(dir)
+    }
+
+    // The mode is only applied on Unix, where permissions are checked
+    fn write(path: &Path, contents: &str, mode: u32) -> anyhow::Result<()> {
+        fs::write(path, contents)?;
+
+        #[cfg(unix)]
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+        #[cfg(not(unix))]
+        let _ = mode;
+
+        Ok(())
+    }
+
+    // Load with the given environment variables rather than the real ones, which other tests read
+    // in parallel
+    fn load_vars(
+        loader: Loader<Secrets>,
+        vars: Vec<(&str, &Path)>,
+    ) -> std::result::Result<Secrets, ConfigError> {
+        let figment = Figment::from(figment::providers::Serialized::defaults(Secrets::default()));
+        let vars = vars
+            .into_iter()
+            .map(|(name, path)| (name.to_string(), path.display().to_string()));
+
+        loader
+            .load_vars(figment, vars)
+            .extract()
+            .map_err(ConfigError::from_figment)
+    }
+
+    fn load(loader: Loader<Secrets>) -> std::result::Result<Secrets, ConfigError> {
+        load_vars(loader, vec![])
+    }
+
+    #[test]
+    fn test_secrets_dir_success() -> anyhow::Result<()> {
+        let dir = temp_dir()?;
+
+        write(&dir.join("SECRETS_TEST_URL"), "postgres://secret\n", 0o600)?;
+        write(&dir.join("secrets_test_max"), "10\n", 0o644)?;
+        write(&dir.join("secrets_test.client.secret"), "shh\r\n", 0o400)?;
+        write(&dir.join("UNKNOWN"), "ignored", 0o600)?;
+        fs::create_dir_all(dir.join("..data"))?;
+
+        let config = load(Loader::default().with_dir(dir.clone()))?;
+
+        assert_eq!("postgres://secret", config.secrets_test.url);
+        assert_eq!(10, config.secrets_test.max);
+        assert_eq!(Some("shh".to_string()), config.secrets_test.client.secret);
+
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_secrets_env_file_success() -> anyhow::Result<()> {
+        let dir = temp_dir()?;
+        let path = dir.join("client_id");
+
+        write(&path, "client\n\n", 0o600)?;
+
+        let config = load_vars(
+            Loader::default(),
+            vec![("SECRETS_TEST_CLIENT_ID_FILE", &path)],
+        )?;
+
+        assert_eq!(Some("client".to_string()), config.secrets_test.client.id);
+
+        // The env Loader leaves the variable alone, because it targets a known key
+        let tree = serde_json::to_value(Secrets::default())?;
+        let target = file_target(
+            &tree,
+            &Secrets::env_options(),
+            UncasedStr::new("SECRETS_TEST_CLIENT_ID_FILE"),
+        );
+
+        assert_eq!(
+            Some("secrets_test.client.id"),
+            target.as_deref().map(|key| key.as_str())
+        );
+
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_secrets_strings() -> anyhow::Result<()> {
+        let dir = temp_dir()?;
+
+        write(&dir.join("SECRETS_TEST_URL"), "[abc]\n", 0o600)?;
+        write(&dir.join("SECRETS_TEST_CLIENT_ID"), "0123", 0o600)?;
+        write(&dir.join("SECRETS_TEST_CLIENT_SECRET"), "true", 0o600)?;
+
+        let config = load(Loader::default().with_dir(dir.clone()))?;
+
+        // Only keys with a number or boolean default are parsed
+        assert_eq!("[abc]", config.secrets_test.url);
+        assert_eq!(Some("0123".to_string()), config.secrets_test.client.id);
+        assert_eq!(Some("true".to_string()), config.secrets_test.client.secret);
+
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_secrets_permissions() -> anyhow::Result<()> {
+        let dir = temp_dir()?;
+
+        write(&dir.join("SECRETS_TEST_URL"), "postgres://secret", 0o666)?;
+
+        let result = load(Loader::default().with_dir(dir.clone()));
+
+        let Err(err) = result else {
+            panic!("did not return Err as expected")
+        };
+        assert_eq!("secrets_test.url", err.errors[0].path);
+        assert!(err.errors[0].message.contains("world-writable"));
+
+        // Readable files are rejected in strict mode
+        write(&dir.join("SECRETS_TEST_URL"), "postgres://secret", 0o644)?;
+
+        assert!(load(Loader::default().with_dir(dir.clone())).is_ok());
+        assert!(load(Loader::default().with_dir(dir.clone()).strict()).is_err());
+
+        fs::remove_dir_all(dir)?;
+
+        Ok(())
+    }
+}
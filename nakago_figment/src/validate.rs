@@ -0,0 +1,350 @@
+use std::fmt::{self, Display};
+
+use figment::{error::Kind, Figment, Metadata};
+
+/// A single invalid or missing Config key
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Invalid {
+    /// The dotted path to the key, such as `auth.url`
+    pub path: String,
+
+    /// A description of the problem
+    pub message: String,
+
+    /// The source that supplied the value, if known
+    pub source: Option<String>,
+}
+
+impl Display for Invalid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)?;
+
+        if let Some(source) = &self.source {
+            write!(f, " (from {source})")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The Config could not be loaded, with every invalid or missing key that was found
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigError {
+    /// The invalid or missing keys
+    pub errors: Vec<Invalid>,
+}
+
+impl ConfigError {
+    /// Collect the errors from a failed extraction, which may be chained together by Figment
+    pub fn from_figment(error: figment::Error) -> Self {
+        let errors = error
+            .into_iter()
+            .map(|error| {
+                let mut path = error.path.clone();
+
+                // Missing fields are reported against the parent section
+                let message = match &error.kind {
+                    Kind::MissingField(field) => {
+                        path.push(field.to_string());
+                        "missing".to_string()
+                    }
+                    kind => kind.to_string(),
+                };
+
+                Invalid {
+                    path: path.join("."),
+                    message,
+                    source: error.metadata.as_ref().map(describe),
+                }
+            })
+            .collect();
+
+        Self { errors }
+    }
+
+    /// Collect the Violations found by a Config's `validate` hook, looking up the source of each
+    /// key in the Figment that the Config was extracted from
+    pub fn from_violations(violations: Violations, figment: &Figment) -> Self {
+        let errors = violations
+            .errors
+            .into_iter()
+            .map(|invalid| Invalid {
+                source: figment.find_metadata(&invalid.path).map(describe),
+                ..invalid
+            })
+            .collect();
+
+        Self { errors }
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid config")?;
+
+        for invalid in &self.errors {
+            write!(f, "\n  - {invalid}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// Describe where a value came from, such as `TOML file /app/config.toml`
+pub(crate) fn describe(metadata: &Metadata) -> String {
+    match &metadata.source {
+        Some(source) => format!("{} {}", metadata.name, source),
+        None => metadata.name.to_string(),
+    }
+}
+
+/// Validate a Config section, adding any problems to the given Violations
+pub trait Validate {
+    /// Check each field, adding a Violation for anything that is invalid
+    fn validate(&self, violations: &mut Violations);
+}
+
+/// Values that can be checked with the `non_empty` rule
+pub trait IsEmpty {
+    /// Whether the value is considered empty
+    fn is_empty_value(&self) -> bool;
+}
+
+impl IsEmpty for str {
+    fn is_empty_value(&self) -> bool {
+        self.trim().is_empty()
+    }
+}
+
+impl IsEmpty for String {
+    fn is_empty_value(&self) -> bool {
+        self.as_str().is_empty_value()
+    }
+}
+
+impl<T> IsEmpty for Vec<T> {
+    fn is_empty_value(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<T: IsEmpty> IsEmpty for Option<T> {
+    fn is_empty_value(&self) -> bool {
+        self.as_ref().is_none_or(IsEmpty::is_empty_value)
+    }
+}
+
+/// The problems found while validating a Config, tracking the dotted path to the current section
+#[derive(Clone, Debug, Default)]
+pub struct Violations {
+    path: Vec<String>,
+    errors: Vec<Invalid>,
+}
+
+impl Violations {
+    /// Add a Violation for a field in the current section
+    pub fn add(&mut self, field: &str, message: impl Into<String>) {
+        let mut path = self.path.clone();
+        path.push(field.to_string());
+
+        self.errors.push(Invalid {
+            path: path.join("."),
+            message: message.into(),
+            source: None,
+        });
+    }
+
+    /// Validate a nested section, prefixing the path of any Violations with the field name
+    pub fn nested<F>(&mut self, field: &str, f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        self.path.push(field.to_string());
+        f(self);
+        self.path.pop();
+    }
+
+    /// Require the value to be within the given bounds, inclusive
+    pub fn range<T: PartialOrd + Display>(
+        &mut self,
+        field: &str,
+        value: &T,
+        min: Option<T>,
+        max: Option<T>,
+    ) {
+        if let Some(min) = min {
+            if *value < min {
+                self.add(field, format!("{value} is less than the minimum of {min}"));
+            }
+        }
+
+        if let Some(max) = max {
+            if *value > max {
+                self.add(
+                    field,
+                    format!("{value} is greater than the maximum of {max}"),
+                );
+            }
+        }
+    }
+
+    /// Require the value to be an absolute URL with a host
+    pub fn url(&mut self, field: &str, value: impl AsRef<str>) {
+        let value = value.as_ref();
+
+        match url::Url::parse(value) {
+            Ok(url) if url.host_str().is_some_and(|host| !host.is_empty()) => {}
+            Ok(_) => self.add(field, format!("\"{value}\" is missing a host")),
+            Err(err) => self.add(field, format!("\"{value}\" is not a valid URL: {err}")),
+        }
+    }
+
+    /// Require the value to be present and not blank
+    pub fn non_empty<T: IsEmpty + ?Sized>(&mut self, field: &str, value: &T) {
+        if value.is_empty_value() {
+            self.add(field, "must not be empty");
+        }
+    }
+
+    /// Whether any Violations were found
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// The Violations that were found
+    pub fn errors(&self) -> &[Invalid] {
+        &self.errors
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use figment::providers::{Format, Serialized, Toml};
+    use pretty_assertions::assert_eq;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+    pub struct Auth {
+        pub url: String,
+        pub audience: String,
+    }
+
+    impl Validate for Auth {
+        fn validate(&self, violations: &mut Violations) {
+            violations.url("url", &self.url);
+            violations.non_empty("audience", &self.audience);
+        }
+    }
+
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+    pub struct Pool {
+        pub min: u32,
+        pub max: u32,
+    }
+
+    #[derive(Default, Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+    pub struct Config {
+        pub auth: Auth,
+        pub pool: Pool,
+    }
+
+    impl Validate for Config {
+        fn validate(&self, violations: &mut Violations) {
+            violations.nested("auth", |violations| self.auth.validate(violations));
+            violations.nested("pool", |violations| {
+                violations.range("max", &self.pool.max, Some(1), Some(100));
+            });
+
+            if self.pool.min > self.pool.max {
+                violations.add("pool.min", "must not be greater than pool.max");
+            }
+        }
+    }
+
+    #[test]
+    fn test_violations_paths() {
+        let config = Config {
+            auth: Auth {
+                url: "https://".to_string(),
+                audience: " ".to_string(),
+            },
+            pool: Pool { min: 5, max: 200 },
+        };
+
+        let mut violations = Violations::default();
+        config.validate(&mut violations);
+
+        let paths: Vec<&str> = violations
+            .errors()
+            .iter()
+            .map(|invalid| invalid.path.as_str())
+            .collect();
+
+        assert_eq!(vec!["auth.url", "auth.audience", "pool.max"], paths);
+
+        let config = Config {
+            auth: Auth {
+                url: "https://example.com".to_string(),
+                audience: "localhost".to_string(),
+            },
+            pool: Pool { min: 5, max: 1 },
+        };
+
+        let mut violations = Violations::default();
+        config.validate(&mut violations);
+
+        assert_eq!(1, violations.errors().len());
+        assert_eq!("pool.min", violations.errors()[0].path);
+    }
+
+    #[test]
+    fn test_config_error_sources() {
+        let figment = Figment::from(Serialized::defaults(Config::default())).merge(Toml::string(
+            r#"
+            [auth]
+            url = "https://"
+            audience = "localhost"
+            "#,
+        ));
+
+        let config: Config = figment.extract().unwrap();
+
+        let mut violations = Violations::default();
+        config.validate(&mut violations);
+
+        let error = ConfigError::from_violations(violations, &figment);
+
+        assert_eq!(2, error.errors.len());
+        assert_eq!("auth.url", error.errors[0].path);
+        assert!(error.errors[0]
+            .source
+            .as_ref()
+            .is_some_and(|source| source.contains("TOML")));
+        assert!(error.to_string().contains("auth.url"));
+    }
+
+    #[test]
+    fn test_config_error_missing() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Required {
+            auth: Auth,
+        }
+
+        let figment = Figment::from(Toml::string(
+            r#"
+            [auth]
+            url = "https://example.com"
+            "#,
+        ));
+
+        let error = ConfigError::from_figment(figment.extract::<Required>().unwrap_err());
+
+        assert_eq!("auth.audience", error.errors[0].path);
+        assert_eq!("missing", error.errors[0].message);
+    }
+}
@@ -0,0 +1,435 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use nakago::{Inject, Result, Tag};
+use tokio::{
+    sync::watch,
+    task::{self, JoinHandle},
+    time::{self, MissedTickBehavior},
+};
+
+use crate::{
+    args::{Args, Override},
+    async_loader::AsyncLoaders,
+    loader::LoadAll,
+    report::Report,
+    Config, Formats, Loaders,
+};
+
+/// A channel that receives each Config that is accepted after a reload
+pub type Updates<C> = watch::Receiver<Arc<C>>;
+
+/// Watch the Config files for changes, reloading and validating the Config each time they are
+/// modified. Accepted changes replace the Config and its `Report` in the container and are sent to
+/// subscribers, while changes that fail to load or validate are logged and ignored.
+///
+/// Run this after `Init`, with the same custom path and Tags.
+pub struct Watch<C: Config> {
+    custom_path: Option<PathBuf>,
+    loaders_tag: Option<&'static Tag<Loaders>>,
+    formats_tag: Option<&'static Tag<Formats>>,
+    async_loaders_tag: Option<&'static Tag<AsyncLoaders>>,
+    config_tag: Option<&'static Tag<C>>,
+    dir: Option<PathBuf>,
+    profile: Option<String>,
+    timeout: Option<Duration>,
+    overrides: Vec<Override>,
+    interval: Duration,
+}
+
+impl<C: Config> Default for Watch<C> {
+    fn default() -> Self {
+        Self {
+            custom_path: None,
+            loaders_tag: None,
+            formats_tag: None,
+            async_loaders_tag: None,
+            config_tag: None,
+            dir: None,
+            profile: None,
+            timeout: None,
+            overrides: Vec::new(),
+            interval: Duration::from_secs(2),
+        }
+    }
+}
+
+impl<C: Config> Watch<C> {
+    /// Watch a custom path in addition to the default Config files
+    pub fn with_path(self, custom_path: PathBuf) -> Self {
+        Self {
+            custom_path: Some(custom_path),
+            ..self
+        }
+    }
+
+    /// Maybe watch a custom path in addition to the default Config files
+    pub fn maybe_with_path(self, custom_path: Option<PathBuf>) -> Self {
+        Self {
+            custom_path,
+            ..self
+        }
+    }
+
+    /// Use the custom path and the `--set` overrides from the command-line arguments. The overrides
+    /// take precedence over every other source.
+    pub fn with_args(self, args: &Args<C>) -> Self {
+        Self {
+            custom_path: args.config.clone().or(self.custom_path),
+            overrides: args.overrides.clone(),
+            ..self
+        }
+    }
+
+    /// Search for the Config files in the given directory rather than the working directory
+    pub fn with_dir(self, dir: PathBuf) -> Self {
+        Self {
+            dir: Some(dir),
+            ..self
+        }
+    }
+
+    /// Use the given profile rather than the value of the `NAKAGO_PROFILE` variable
+    pub fn with_profile(self, profile: impl Into<String>) -> Self {
+        Self {
+            profile: Some(profile.into()),
+            ..self
+        }
+    }
+
+    /// Use a Config Tag when replacing the reloaded Config
+    pub fn with_config_tag(self, tag: &'static Tag<C>) -> Self {
+        Self {
+            config_tag: Some(tag),
+            ..self
+        }
+    }
+
+    /// Use a Loaders Tag when retrieving the current Loaders
+    pub fn with_loaders_tag(self, tag: &'static Tag<Loaders>) -> Self {
+        Self {
+            loaders_tag: Some(tag),
+            ..self
+        }
+    }
+
+    /// Use a Formats Tag when retrieving the additional file Formats
+    pub fn with_formats_tag(self, tag: &'static Tag<Formats>) -> Self {
+        Self {
+            formats_tag: Some(tag),
+            ..self
+        }
+    }
+
+    /// Use an AsyncLoaders Tag when retrieving the current AsyncLoaders
+    pub fn with_async_loaders_tag(self, tag: &'static Tag<AsyncLoaders>) -> Self {
+        Self {
+            async_loaders_tag: Some(tag),
+            ..self
+        }
+    }
+
+    /// How long each AsyncLoader may run on reload, which defaults to 10 seconds
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// How often to check the files for changes, which defaults to 2 seconds
+    pub fn with_interval(self, interval: Duration) -> Self {
+        Self { interval, ..self }
+    }
+
+    /// Start watching in a background task, which runs until the returned Watcher is dropped. The
+    /// `Updates<C>` channel is also injected into the container for other components to subscribe.
+    pub async fn start(&self, i: &Inject) -> Result<Watcher<C>> {
+        let loaders_result = match self.loaders_tag {
+            Some(tag) => i.get_tag(tag).await,
+            None => i.get::<Loaders>().await,
+        };
+
+        let loaders = loaders_result.unwrap_or_default().to_vec();
+
+        let formats_result = match self.formats_tag {
+            Some(tag) => i.get_tag(tag).await,
+            None => i.get::<Formats>().await,
+        };
+
+        let formats = formats_result.unwrap_or_default().to_vec();
+
+        let async_loaders_result = match self.async_loaders_tag {
+            Some(tag) => i.get_tag(tag).await,
+            None => i.get::<AsyncLoaders>().await,
+        };
+
+        let async_loaders = async_loaders_result.unwrap_or_default().to_vec();
+
+        let current = match self.config_tag {
+            Some(tag) => i.get_tag(tag).await?,
+            None => i.get::<C>().await?,
+        };
+
+        let (sender, updates) = watch::channel(current);
+
+        let _ = i.override_type::<Updates<C>>(updates.clone()).await?;
+
+        let mut loader = LoadAll::<C>::new(loaders)
+            .with_formats(formats)
+            .with_async_loaders(async_loaders)
+            .with_overrides(self.overrides.clone());
+        if let Some(dir) = &self.dir {
+            loader = loader.with_dir(dir.clone());
+        }
+        if let Some(profile) = &self.profile {
+            loader = loader.with_profile(profile.clone());
+        }
+        if let Some(timeout) = self.timeout {
+            loader = loader.with_timeout(timeout);
+        }
+
+        // Take the initial stamps before returning, so that no changes are missed
+        let current = stamps(&loader.paths(self.custom_path.clone()));
+
+        let reload = Reload {
+            i: i.clone(),
+            loader: Arc::new(loader),
+            custom_path: self.custom_path.clone(),
+            config_tag: self.config_tag,
+            sender,
+        };
+
+        let handle = tokio::spawn(reload.run(current, self.interval));
+
+        Ok(Watcher { updates, handle })
+    }
+}
+
+/// A handle to a running Config Watch, which stops watching when dropped
+pub struct Watcher<C: Config> {
+    updates: Updates<C>,
+    handle: JoinHandle<()>,
+}
+
+impl<C: Config> Watcher<C> {
+    /// Subscribe to the Configs accepted after each reload
+    pub fn subscribe(&self) -> Updates<C> {
+        self.updates.clone()
+    }
+}
+
+impl<C: Config> Drop for Watcher<C> {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+struct Reload<C: Config> {
+    i: Inject,
+    loader: Arc<LoadAll<C>>,
+    custom_path: Option<PathBuf>,
+    config_tag: Option<&'static Tag<C>>,
+    sender: watch::Sender<Arc<C>>,
+}
+
+impl<C: Config> Reload<C> {
+    async fn run(self, mut current: Vec<(PathBuf, Stamp)>, interval: Duration) {
+        let mut ticker = time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+        loop {
+            ticker.tick().await;
+
+            let next = match self.stamps().await {
+                Ok(next) => next,
+                Err(err) => {
+                    log::error!("Unable to check the Config files for changes: {err}");
+                    continue;
+                }
+            };
+
+            if next == current {
+                continue;
+            }
+
+            current = next;
+
+            if let Err(err) = self.reload().await {
+                log::error!("Rejected a Config change, keeping the current Config: {err}");
+            }
+        }
+    }
+
+    // The paths are listed again each time, in case fragments were added or removed
+    async fn stamps(&self) -> anyhow::Result<Vec<(PathBuf, Stamp)>> {
+        let loader = self.loader.clone();
+        let custom_path = self.custom_path.clone();
+
+        Ok(task::spawn_blocking(move || stamps(&loader.paths(custom_path))).await?)
+    }
+
+    async fn reload(&self) -> anyhow::Result<()> {
+        let loader = self.loader.clone();
+        let custom_path = self.custom_path.clone();
+
+        // The files are read on the blocking pool, and the AsyncLoaders then run on the runtime
+        let sources = task::spawn_blocking(move || loader.load_sources(custom_path)).await?;
+        let (config, figment) = self.loader.extract_sources_async(sources).await?;
+
+        let _ = self
+            .i
+            .override_type::<Report>(Report::new(&config, &figment))
+            .await?;
+
+        match self.config_tag {
+            Some(tag) => self.i.replace_tag(tag, config.clone()).await?,
+            None => self.i.replace::<C>(config.clone()).await?,
+        }
+
+        self.sender.send_replace(Arc::new(config));
+
+        log::info!("Reloaded the Config");
+
+        Ok(())
+    }
+}
+
+// The modification time and length of a file, or None if it doesn't exist
+type Stamp = Option<(SystemTime, u64)>;
+
+fn stamps(paths: &[PathBuf]) -> Vec<(PathBuf, Stamp)> {
+    paths
+        .iter()
+        .map(|path| {
+            let stamp = fs::metadata(path)
+                .ok()
+                .and_then(|metadata| Some((metadata.modified().ok()?, metadata.len())));
+
+            (path.clone(), stamp)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use nakago_derive::{Config, Validate};
+    use pretty_assertions::assert_eq;
+    use serde::{Deserialize, Serialize};
+
+    use crate::Init;
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Config, Validate)]
+    #[config(validate)]
+    pub struct Settings {
+        #[validate(non_empty)]
+        pub level: String,
+    }
+
+    impl Default for Settings {
+        fn default() -> Self {
+            Self {
+                level: "info".to_string(),
+            }
+        }
+    }
+
+    pub const SETTINGS: Tag<Settings> = Tag::new("watch::test::Settings");
+
+    fn temp_path() -> PathBuf {
+        std::env::temp_dir().join(format!("nakago-watch-{}.toml", ulid::Ulid::new()))
+    }
+
+    #[tokio::test]
+    async fn test_watch_reload_success() -> anyhow::Result<()> {
+        let i = Inject::default();
+        let path = temp_path();
+
+        fs::write(&path, "level = \"warn\"\n")?;
+
+        Init::<Settings>::default()
+            .with_path(path.clone())
+            .init(&i)
+            .await?;
+
+        let watcher = Watch::<Settings>::default()
+            .with_path(path.clone())
+            .with_interval(Duration::from_millis(10))
+            .start(&i)
+            .await?;
+
+        let mut updates = watcher.subscribe();
+        assert_eq!("warn", updates.borrow().level);
+
+        fs::write(&path, "level = \"debug\"\n")?;
+
+        time::timeout(Duration::from_secs(5), updates.changed()).await??;
+        assert_eq!("debug", updates.borrow().level);
+
+        let config = i.get::<Settings>().await?;
+        assert_eq!("debug", config.level);
+
+        // The Report is rebuilt for the reloaded Config
+        let report = i.get::<Report>().await?;
+        let level = report.get("level").map(|entry| entry.value.clone());
+        assert_eq!(Some(serde_json::Value::from("debug")), level);
+
+        // The channel is available in the container as well
+        let injected = i.get::<Updates<Settings>>().await?;
+        assert_eq!("debug", injected.borrow().level);
+
+        fs::remove_file(&path)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_watch_reload_rejected() -> anyhow::Result<()> {
+        let i = Inject::default();
+        let path = temp_path();
+
+        fs::write(&path, "level = \"warn\"\n")?;
+
+        Init::<Settings>::default()
+            .with_path(path.clone())
+            .with_config_tag(&SETTINGS)
+            .init(&i)
+            .await?;
+
+        let watcher = Watch::<Settings>::default()
+            .with_path(path.clone())
+            .with_config_tag(&SETTINGS)
+            .with_interval(Duration::from_millis(10))
+            .start(&i)
+            .await?;
+
+        let mut updates = watcher.subscribe();
+
+        // An invalid edit is rejected
+        fs::write(&path, "level = \"\"\n")?;
+
+        let result = time::timeout(Duration::from_millis(200), updates.changed()).await;
+        assert!(result.is_err());
+
+        let config = i.get_tag(&SETTINGS).await?;
+        assert_eq!("warn", config.level);
+
+        // A following valid edit is accepted
+        fs::write(&path, "level = \"error\"\n")?;
+
+        time::timeout(Duration::from_secs(5), updates.changed()).await??;
+
+        let config = i.get_tag(&SETTINGS).await?;
+        assert_eq!("error", config.level);
+
+        fs::remove_file(&path)?;
+
+        Ok(())
+    }
+}
@@ -3,10 +3,13 @@ use std::sync::{Arc, Mutex};
 use async_trait::async_trait;
 use nakago::{self, provider, to_provider_error, Inject, Provider, Tag};
 use nakago_derive::Provider;
-use nakago_figment::FromRef;
-use sea_orm::{DatabaseBackend, DatabaseConnection, MockDatabase, MockDatabaseTrait};
+use nakago_figment::{named, FromRef, Named};
+use sea_orm::{
+    ConnectionTrait, DatabaseBackend, DatabaseConnection, DbBackend, DbErr, ExecResult,
+    MockDatabase, MockDatabaseTrait, QueryResult, Statement,
+};
 
-use crate::Config;
+use crate::{instrument::Instrumented, transaction::Transaction, Config};
 
 /// Tag(nakago_sea_orm::DatabaseConnection)
 pub const CONNECTION: Tag<DatabaseConnection> = Tag::new("nakago_sea_orm::DatabaseConnection");
@@ -47,13 +50,211 @@ where
         let config = Config::from_ref(&*dep);
 
         Ok(Arc::new(
-            sea_orm::Database::connect(&config.url)
+            sea_orm::Database::connect(config.connect_options())
                 .await
                 .map_err(to_provider_error)?,
         ))
     }
 }
 
+/// Provide a SeaOrm Database connection for a named instance in a map-shaped database section, such
+/// as `[database.analytics]`
+pub struct ProvideNamed<C: nakago_figment::Config> {
+    name: String,
+    config_tag: Option<&'static Tag<C>>,
+}
+
+impl<C: nakago_figment::Config> ProvideNamed<C> {
+    /// Create a new instance of ProvideNamed for the given instance name
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            config_tag: None,
+        }
+    }
+
+    /// Set the config Tag for this instance
+    pub fn with_config_tag(self, config_tag: &'static Tag<C>) -> Self {
+        Self {
+            config_tag: Some(config_tag),
+            ..self
+        }
+    }
+}
+
+#[Provider]
+#[async_trait]
+impl<C: nakago_figment::Config> Provider<DatabaseConnection> for ProvideNamed<C>
+where
+    Named<Config>: FromRef<C>,
+{
+    async fn provide(self: Arc<Self>, i: Inject) -> provider::Result<Arc<DatabaseConnection>> {
+        let config: Config = named::instance(&i, self.config_tag, &self.name).await?;
+
+        Ok(Arc::new(
+            sea_orm::Database::connect(config.connect_options())
+                .await
+                .map_err(to_provider_error)?,
+        ))
+    }
+}
+
+/// Provide a connection for each of the given database instances, under the `named::tag` for each
+/// instance name, such as `named::tag::<DatabaseConnection>("analytics")`
+pub async fn provide_named<C: nakago_figment::Config>(
+    i: &Inject,
+    names: &[&str],
+) -> nakago::Result<()>
+where
+    Named<Config>: FromRef<C>,
+{
+    for name in names {
+        i.provide_tag(
+            named::tag::<DatabaseConnection>(name),
+            ProvideNamed::<C>::new(name),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// A database Connection for services, which is either the shared connection pool, a
+/// request-scoped Transaction, or one of those wrapped with tracing instrumentation. Services that
+/// hold a Connection can be pointed at the request Transaction so that their writes are committed
+/// or rolled back together.
+#[derive(Clone, Debug)]
+pub enum Connection {
+    /// The shared connection pool
+    Pool(Arc<DatabaseConnection>),
+
+    /// A request-scoped Transaction
+    Transaction(Transaction),
+
+    /// A Connection that records a tracing span for each statement
+    Instrumented(Arc<Instrumented>),
+}
+
+impl Connection {
+    /// Get a Connection that uses the given request Transaction, keeping the instrumentation if
+    /// this Connection is Instrumented
+    pub fn with_transaction(&self, txn: Transaction) -> Connection {
+        match self {
+            Connection::Instrumented(db) => db.with_transaction(txn).into(),
+            Connection::Pool(_) | Connection::Transaction(_) => txn.into(),
+        }
+    }
+}
+
+impl From<Arc<DatabaseConnection>> for Connection {
+    fn from(db: Arc<DatabaseConnection>) -> Self {
+        Connection::Pool(db)
+    }
+}
+
+impl From<Transaction> for Connection {
+    fn from(txn: Transaction) -> Self {
+        Connection::Transaction(txn)
+    }
+}
+
+impl From<Instrumented> for Connection {
+    fn from(db: Instrumented) -> Self {
+        Connection::Instrumented(Arc::new(db))
+    }
+}
+
+impl From<Arc<Instrumented>> for Connection {
+    fn from(db: Arc<Instrumented>) -> Self {
+        Connection::Instrumented(db)
+    }
+}
+
+#[async_trait]
+impl ConnectionTrait for Connection {
+    fn get_database_backend(&self) -> DbBackend {
+        match self {
+            Connection::Pool(db) => db.get_database_backend(),
+            Connection::Transaction(txn) => txn.get_database_backend(),
+            Connection::Instrumented(db) => db.get_database_backend(),
+        }
+    }
+
+    async fn execute(&self, stmt: Statement) -> Result<ExecResult, DbErr> {
+        match self {
+            Connection::Pool(db) => db.execute(stmt).await,
+            Connection::Transaction(txn) => txn.execute(stmt).await,
+            Connection::Instrumented(db) => db.execute(stmt).await,
+        }
+    }
+
+    async fn execute_unprepared(&self, sql: &str) -> Result<ExecResult, DbErr> {
+        match self {
+            Connection::Pool(db) => db.execute_unprepared(sql).await,
+            Connection::Transaction(txn) => txn.execute_unprepared(sql).await,
+            Connection::Instrumented(db) => db.execute_unprepared(sql).await,
+        }
+    }
+
+    async fn query_one(&self, stmt: Statement) -> Result<Option<QueryResult>, DbErr> {
+        match self {
+            Connection::Pool(db) => db.query_one(stmt).await,
+            Connection::Transaction(txn) => txn.query_one(stmt).await,
+            Connection::Instrumented(db) => db.query_one(stmt).await,
+        }
+    }
+
+    async fn query_all(&self, stmt: Statement) -> Result<Vec<QueryResult>, DbErr> {
+        match self {
+            Connection::Pool(db) => db.query_all(stmt).await,
+            Connection::Transaction(txn) => txn.query_all(stmt).await,
+            Connection::Instrumented(db) => db.query_all(stmt).await,
+        }
+    }
+
+    fn is_mock_connection(&self) -> bool {
+        match self {
+            Connection::Pool(db) => db.is_mock_connection(),
+            Connection::Transaction(txn) => txn.is_mock_connection(),
+            Connection::Instrumented(db) => db.is_mock_connection(),
+        }
+    }
+}
+
+/// Provide a Connection that uses the shared DatabaseConnection pool
+#[derive(Default)]
+pub struct ProvidePool {
+    connection_tag: Option<&'static Tag<DatabaseConnection>>,
+}
+
+impl ProvidePool {
+    /// Create a new instance of ProvidePool
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the DatabaseConnection Tag for this instance
+    pub fn with_connection_tag(self, connection_tag: &'static Tag<DatabaseConnection>) -> Self {
+        Self {
+            connection_tag: Some(connection_tag),
+        }
+    }
+}
+
+#[Provider]
+#[async_trait]
+impl Provider<Connection> for ProvidePool {
+    async fn provide(self: Arc<Self>, i: Inject) -> provider::Result<Arc<Connection>> {
+        let db = if let Some(tag) = self.connection_tag {
+            i.get_tag(tag).await?
+        } else {
+            i.get::<DatabaseConnection>().await?
+        };
+
+        Ok(Arc::new(Connection::Pool(db)))
+    }
+}
+
 /// Provide a Mock Database Connection for use in unit testing
 pub struct ProvideMock {
     db: Mutex<MockDatabase>,
@@ -93,3 +294,89 @@ impl Provider<DatabaseConnection> for ProvideMock {
         Ok(Arc::new(db.into_connection()))
     }
 }
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::path::PathBuf;
+
+    use nakago_derive::Config;
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::config;
+
+    #[derive(Clone, Debug, Default, Serialize, Deserialize, Config)]
+    pub struct Databases {
+        pub database: Named<Config>,
+    }
+
+    fn instance(path: &std::path::Path) -> Config {
+        Config {
+            url: format!("sqlite://{}?mode=rwc", path.display()),
+            schema: None,
+            ..config::test::config()
+        }
+    }
+
+    // The file that a SQLite connection has open
+    async fn file(db: &DatabaseConnection) -> anyhow::Result<String> {
+        let row = db
+            .query_one(Statement::from_string(
+                DbBackend::Sqlite,
+                "PRAGMA database_list",
+            ))
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("no database"))?;
+
+        Ok(row.try_get("", "file")?)
+    }
+
+    #[tokio::test]
+    async fn test_provide_named_success() -> anyhow::Result<()> {
+        let i = Inject::default();
+        let dir = std::env::temp_dir();
+
+        let paths: Vec<PathBuf> = ["primary", "analytics"]
+            .iter()
+            .map(|name| dir.join(format!("nakago-{name}-{}.db", ulid::Ulid::new())))
+            .collect();
+
+        let database = Named::default()
+            .with("primary", instance(&paths[0]))
+            .with("analytics", instance(&paths[1]));
+
+        i.inject(Databases { database }).await?;
+
+        provide_named::<Databases>(&i, &["primary", "analytics"]).await?;
+
+        let primary = i
+            .get_tag(named::tag::<DatabaseConnection>("primary"))
+            .await?;
+        let analytics = i
+            .get_tag(named::tag::<DatabaseConnection>("analytics"))
+            .await?;
+
+        assert!(file(&primary)
+            .await?
+            .ends_with(&*paths[0].to_string_lossy()));
+        assert!(file(&analytics)
+            .await?
+            .ends_with(&*paths[1].to_string_lossy()));
+
+        // An unknown instance name fails
+        i.provide_tag(
+            named::tag::<DatabaseConnection>("missing"),
+            ProvideNamed::<Databases>::new("missing"),
+        )
+        .await?;
+
+        let result = i.get_tag(named::tag::<DatabaseConnection>("missing")).await;
+        assert!(result.is_err());
+
+        for path in paths {
+            let _ = std::fs::remove_file(path);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,370 @@
+use nakago_axum::utils::{
+    decode_cursor, encode_cursor, CursorArgs, CursorResponse, Edge, InvalidCursor, Ordering,
+};
+use sea_orm::{
+    sea_query::IntoValueTuple, ConnectionTrait, DbErr, EntityTrait, FromQueryResult, IntoIdentity,
+    PaginatorTrait, Select,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// The number of records returned when neither `first` nor `last` is given
+pub const DEFAULT_PAGE_SIZE: u64 = 20;
+
+/// The largest number of records returned in one page. Larger `first` or `last` values are
+/// reduced to this.
+pub const MAX_PAGE_SIZE: u64 = 100;
+
+/// An error fetching a page of records
+#[derive(Debug, thiserror::Error)]
+pub enum PaginateError {
+    /// An `after` or `before` cursor couldn't be decoded, usually because of a client error
+    #[error("{0}")]
+    InvalidCursor(#[from] InvalidCursor),
+
+    /// Both `first` and `last` were given
+    #[error("first and last can't be used together")]
+    FirstAndLast,
+
+    /// The query failed
+    #[error(transparent)]
+    Database(#[from] DbErr),
+}
+
+/// Fetch a page of records with keyset pagination, following the Relay connection spec.
+///
+/// The records are ordered by the given columns, such as `Ordering::Desc((Column::CreatedAt,
+/// Column::Id))`, and the last column should be unique so that every record has a distinct
+/// position. The `key` function returns the values of those same columns for a record, which are
+/// encoded as its opaque cursor.
+///
+/// `has_next_page` is determined by fetching one extra record when paging forwards, and is true
+/// when a `before` cursor is given while paging backwards. `has_previous_page` works the same way
+/// in reverse. The total is only counted when `args.total` is set.
+///
+/// When neither `first` nor `last` is given, the first `DEFAULT_PAGE_SIZE` records are returned,
+/// and neither may be larger than `MAX_PAGE_SIZE`.
+pub async fn paginate<E, C, I, K, F>(
+    db: &C,
+    query: Select<E>,
+    order_by: Ordering<I>,
+    key: F,
+    args: &CursorArgs,
+) -> Result<CursorResponse<E::Model>, PaginateError>
+where
+    E: EntityTrait,
+    E::Model: FromQueryResult + Sync,
+    C: ConnectionTrait,
+    I: IntoIdentity,
+    K: Serialize + DeserializeOwned + IntoValueTuple,
+    F: Fn(&E::Model) -> K,
+{
+    if args.first.is_some() && args.last.is_some() {
+        return Err(PaginateError::FirstAndLast);
+    }
+
+    let after = args.after.as_deref().map(decode_cursor::<K>).transpose()?;
+    let before = args.before.as_deref().map(decode_cursor::<K>).transpose()?;
+
+    let last = args.last.map(|last| last.min(MAX_PAGE_SIZE));
+    let first = match last {
+        Some(_) => None,
+        None => Some(args.first.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE)),
+    };
+
+    let total = if args.total {
+        Some(query.clone().count(db).await?)
+    } else {
+        None
+    };
+
+    let (mut cursor, descending) = match order_by {
+        Ordering::Asc(columns) => (query.cursor_by(columns), false),
+        Ordering::Desc(columns) => (query.cursor_by(columns), true),
+    };
+
+    if descending {
+        cursor.desc();
+    }
+
+    if let Some(after) = after {
+        cursor.after(after);
+    }
+
+    if let Some(before) = before {
+        cursor.before(before);
+    }
+
+    // Fetch one more record than requested to find out if there is another page
+    if let Some(last) = last {
+        cursor.last(last + 1);
+    } else if let Some(first) = first {
+        cursor.first(first + 1);
+    }
+
+    let mut records = cursor.all(db).await?;
+
+    let mut has_previous_page = args.after.is_some();
+    let mut has_next_page = args.before.is_some();
+
+    if let Some(last) = last {
+        let extra = records
+            .len()
+            .saturating_sub(usize::try_from(last).unwrap_or(usize::MAX));
+
+        // The records are returned in order, so the extra record is at the start
+        has_previous_page = extra > 0;
+        records.drain(..extra);
+    } else if let Some(first) = first {
+        let first = usize::try_from(first).unwrap_or(usize::MAX);
+
+        has_next_page = records.len() > first;
+        records.truncate(first);
+    }
+
+    let edges = records
+        .into_iter()
+        .map(|node| Edge {
+            cursor: encode_cursor(&key(&node)),
+            node,
+        })
+        .collect();
+
+    Ok(CursorResponse::new(
+        edges,
+        has_previous_page,
+        has_next_page,
+        total,
+    ))
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::collections::BTreeMap;
+
+    use pretty_assertions::assert_eq;
+    use sea_orm::{entity::prelude::*, DbBackend};
+
+    use super::*;
+    use crate::mock::MockHandle;
+
+    mod shows {
+        use sea_orm::entity::prelude::*;
+
+        #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+        #[sea_orm(table_name = "shows")]
+        pub struct Model {
+            #[sea_orm(primary_key, auto_increment = false)]
+            pub id: String,
+            pub title: String,
+        }
+
+        #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+        pub enum Relation {}
+
+        impl ActiveModelBehavior for ActiveModel {}
+    }
+
+    fn show(id: &str) -> shows::Model {
+        shows::Model {
+            id: id.to_string(),
+            title: format!("Show {id}"),
+        }
+    }
+
+    fn key(show: &shows::Model) -> String {
+        show.id.clone()
+    }
+
+    #[tokio::test]
+    async fn test_paginate_forwards() -> anyhow::Result<()> {
+        let mock = MockHandle::new(DbBackend::Postgres);
+        let db = mock.connection();
+
+        mock.append_query_results([vec![show("2"), show("3"), show("4")]]);
+
+        let args = CursorArgs {
+            first: Some(2),
+            after: Some(encode_cursor(&"1")),
+            ..Default::default()
+        };
+
+        let page = paginate(
+            &db,
+            shows::Entity::find(),
+            Ordering::Asc(shows::Column::Id),
+            key,
+            &args,
+        )
+        .await?;
+
+        assert_eq!(
+            vec![show("2"), show("3")],
+            page.edges
+                .into_iter()
+                .map(|edge| edge.node)
+                .collect::<Vec<_>>()
+        );
+        assert!(page.page_info.has_next_page);
+        assert!(page.page_info.has_previous_page);
+        assert_eq!(Some(encode_cursor(&"3")), page.page_info.end_cursor);
+        assert_eq!(None, page.total);
+
+        mock.assert_statements(&[
+            r#"SELECT "shows"."id", "shows"."title" FROM "shows" WHERE "shows"."id" > $1 ORDER BY "shows"."id" ASC LIMIT $2"#,
+        ]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_paginate_backwards() -> anyhow::Result<()> {
+        let mock = MockHandle::new(DbBackend::Postgres);
+        let db = mock.connection();
+
+        // The total count, and then the records in the reverse of the requested order
+        mock.append_query_results([vec![BTreeMap::from([("num_items", Value::from(3_i64))])]])
+            .append_query_results([vec![show("1"), show("2"), show("3")]]);
+
+        let args = CursorArgs {
+            last: Some(2),
+            total: true,
+            ..Default::default()
+        };
+
+        let page = paginate(
+            &db,
+            shows::Entity::find(),
+            Ordering::Desc(shows::Column::Id),
+            key,
+            &args,
+        )
+        .await?;
+
+        assert_eq!(
+            vec![show("2"), show("1")],
+            page.edges
+                .into_iter()
+                .map(|edge| edge.node)
+                .collect::<Vec<_>>()
+        );
+        assert!(page.page_info.has_previous_page);
+        assert!(!page.page_info.has_next_page);
+        assert_eq!(Some(3), page.total);
+
+        let args = CursorArgs {
+            first: Some(1),
+            last: Some(1),
+            ..Default::default()
+        };
+
+        let result = paginate(
+            &db,
+            shows::Entity::find(),
+            Ordering::Asc(shows::Column::Id),
+            key,
+            &args,
+        )
+        .await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    // The LIMIT sent with each statement so far
+    fn limits(mock: &MockHandle) -> Vec<Value> {
+        mock.transaction_log()
+            .iter()
+            .flat_map(|txn| txn.statements().to_vec())
+            .filter_map(|stmt| stmt.values.and_then(|values| values.0.last().cloned()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_paginate_page_size() -> anyhow::Result<()> {
+        let mock = MockHandle::new(DbBackend::Postgres);
+        let db = mock.connection();
+
+        mock.append_query_results([vec![show("1"), show("2")]])
+            .append_query_results([vec![show("1"), show("2")]])
+            .append_query_results([vec![show("1"), show("2")]]);
+
+        for args in [
+            CursorArgs::default(),
+            CursorArgs {
+                first: Some(1000),
+                ..Default::default()
+            },
+            CursorArgs {
+                last: Some(1000),
+                ..Default::default()
+            },
+        ] {
+            let page = paginate(
+                &db,
+                shows::Entity::find(),
+                Ordering::Asc(shows::Column::Id),
+                key,
+                &args,
+            )
+            .await?;
+
+            assert_eq!(2, page.edges.len());
+            assert!(!page.page_info.has_next_page);
+            assert!(!page.page_info.has_previous_page);
+        }
+
+        // One more than the page size, to find out if there is another page
+        assert_eq!(
+            vec![
+                Value::from(DEFAULT_PAGE_SIZE + 1),
+                Value::from(MAX_PAGE_SIZE + 1),
+                Value::from(MAX_PAGE_SIZE + 1),
+            ],
+            limits(&mock)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_paginate_invalid_args() -> anyhow::Result<()> {
+        let mock = MockHandle::new(DbBackend::Postgres);
+        let db = mock.connection();
+
+        let args = CursorArgs {
+            after: Some("not a cursor".to_string()),
+            ..Default::default()
+        };
+
+        let result = paginate(
+            &db,
+            shows::Entity::find(),
+            Ordering::Asc(shows::Column::Id),
+            key,
+            &args,
+        )
+        .await;
+        assert!(matches!(result, Err(PaginateError::InvalidCursor(_))));
+
+        let args = CursorArgs {
+            first: Some(1),
+            last: Some(1),
+            ..Default::default()
+        };
+
+        let result = paginate(
+            &db,
+            shows::Entity::find(),
+            Ordering::Asc(shows::Column::Id),
+            key,
+            &args,
+        )
+        .await;
+        assert!(matches!(result, Err(PaginateError::FirstAndLast)));
+
+        // Nothing is sent to the database
+        assert!(mock.statements().is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,548 @@
+use std::{
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use log::Level;
+use nakago::{provider, Inject, Provider, Tag};
+use nakago_derive::Provider;
+use nakago_figment::FromRef;
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DbBackend, DbErr, ExecResult, QueryResult, Statement,
+};
+use tracing::{field::Empty, Instrument};
+
+use crate::{Config, Connection, Transaction};
+
+/// The default threshold for logging a statement as slow
+pub const DEFAULT_SLOW_THRESHOLD: Duration = Duration::from_secs(1);
+
+/// The tracing target for the per-statement duration histogram events
+pub const METRICS_TARGET: &str = "nakago_sea_orm::metrics";
+
+/// Settings for an Instrumented connection
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// Statements that run for at least this long are logged at WARN
+    pub slow_threshold: Duration,
+
+    /// The level to log every statement at, if any
+    pub log_level: Option<Level>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            slow_threshold: DEFAULT_SLOW_THRESHOLD,
+            log_level: None,
+        }
+    }
+}
+
+impl From<&Config> for Options {
+    fn from(config: &Config) -> Self {
+        let log_level = config.debug.then(|| {
+            config
+                .log_level()
+                .and_then(|level| level.to_level())
+                .unwrap_or(Level::Info)
+        });
+
+        Self {
+            slow_threshold: config
+                .slow_threshold_ms
+                .map_or(DEFAULT_SLOW_THRESHOLD, Duration::from_millis),
+            log_level,
+        }
+    }
+}
+
+/// A Connection that runs each statement within a `db.statement` tracing span, recording the SQL,
+/// the number of parameters, the number of rows and the duration. Parameter values are never
+/// recorded.
+///
+/// Statements that run for longer than the slow threshold are logged at WARN. Each statement also
+/// emits a `histogram.db.statement.duration_ms` event at TRACE level on the
+/// `nakago_sea_orm::metrics` target, for a metrics layer such as the one in `tracing-opentelemetry`
+/// to aggregate. Its only attribute is the `db.operation`, such as "SELECT", so that the number of
+/// distinct series stays bounded.
+#[derive(Clone, Debug)]
+pub struct Instrumented {
+    db: Connection,
+    options: Arc<Options>,
+}
+
+impl Instrumented {
+    /// Instrument the given Connection
+    pub fn new(db: impl Into<Connection>, options: Options) -> Self {
+        Self {
+            db: db.into(),
+            options: Arc::new(options),
+        }
+    }
+
+    /// Get a copy that instruments the given request Transaction with the same Options
+    pub fn with_transaction(&self, txn: Transaction) -> Self {
+        Self {
+            db: txn.into(),
+            options: self.options.clone(),
+        }
+    }
+
+    /// The Options used for each statement
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    async fn run<T, F>(
+        &self,
+        sql: &str,
+        params: usize,
+        statement: F,
+        rows: impl FnOnce(&T) -> u64,
+    ) -> Result<T, DbErr>
+    where
+        F: Future<Output = Result<T, DbErr>>,
+    {
+        let span = tracing::debug_span!(
+            "db.statement",
+            db.statement = sql,
+            db.params = params,
+            db.rows = Empty,
+            duration_ms = Empty,
+        );
+
+        let started = Instant::now();
+        let result = statement.instrument(span.clone()).await;
+        let elapsed = started.elapsed();
+
+        let rows = result.as_ref().ok().map(rows);
+        let duration_ms = elapsed.as_secs_f64() * 1000.0;
+
+        span.record("db.rows", rows);
+        span.record("duration_ms", duration_ms);
+
+        span.in_scope(|| self.report(sql, params, rows, elapsed, duration_ms));
+
+        result
+    }
+
+    fn report(
+        &self,
+        sql: &str,
+        params: usize,
+        rows: Option<u64>,
+        elapsed: Duration,
+        duration_ms: f64,
+    ) {
+        tracing::event!(
+            target: METRICS_TARGET,
+            tracing::Level::TRACE,
+            histogram.db.statement.duration_ms = duration_ms,
+            db.operation = operation(sql)
+        );
+
+        if elapsed >= self.options.slow_threshold {
+            tracing::warn!(
+                db.statement = sql,
+                db.params = params,
+                db.rows = rows,
+                duration_ms,
+                "Slow database statement"
+            );
+        }
+
+        // Tracing levels must be known at compile time, so each level gets its own event
+        macro_rules! statement {
+            ($level:expr) => {
+                tracing::event!(
+                    $level,
+                    db.statement = sql,
+                    db.params = params,
+                    db.rows = rows,
+                    duration_ms,
+                    "Database statement"
+                )
+            };
+        }
+
+        match self.options.log_level {
+            Some(Level::Error) => statement!(tracing::Level::ERROR),
+            Some(Level::Warn) => statement!(tracing::Level::WARN),
+            Some(Level::Info) => statement!(tracing::Level::INFO),
+            Some(Level::Debug) => statement!(tracing::Level::DEBUG),
+            Some(Level::Trace) => statement!(tracing::Level::TRACE),
+            None => {}
+        }
+    }
+}
+
+// The kind of statement, from a fixed set so that it can be used as a metrics attribute
+fn operation(sql: &str) -> &'static str {
+    let keyword = sql.split_whitespace().next().unwrap_or_default();
+
+    [
+        "SELECT", "INSERT", "UPDATE", "DELETE", "WITH", "BEGIN", "COMMIT", "ROLLBACK",
+    ]
+    .into_iter()
+    .find(|operation| keyword.eq_ignore_ascii_case(operation))
+    .unwrap_or("OTHER")
+}
+
+// The number of parameters bound to a statement
+fn params(stmt: &Statement) -> usize {
+    stmt.values.as_ref().map_or(0, |values| values.0.len())
+}
+
+#[async_trait]
+impl ConnectionTrait for Instrumented {
+    fn get_database_backend(&self) -> DbBackend {
+        self.db.get_database_backend()
+    }
+
+    async fn execute(&self, stmt: Statement) -> Result<ExecResult, DbErr> {
+        let sql = stmt.sql.clone();
+        let params = params(&stmt);
+
+        self.run(
+            &sql,
+            params,
+            self.db.execute(stmt),
+            ExecResult::rows_affected,
+        )
+        .await
+    }
+
+    async fn execute_unprepared(&self, sql: &str) -> Result<ExecResult, DbErr> {
+        self.run(
+            sql,
+            0,
+            self.db.execute_unprepared(sql),
+            ExecResult::rows_affected,
+        )
+        .await
+    }
+
+    async fn query_one(&self, stmt: Statement) -> Result<Option<QueryResult>, DbErr> {
+        let sql = stmt.sql.clone();
+        let params = params(&stmt);
+
+        self.run(&sql, params, self.db.query_one(stmt), |row| {
+            u64::from(row.is_some())
+        })
+        .await
+    }
+
+    async fn query_all(&self, stmt: Statement) -> Result<Vec<QueryResult>, DbErr> {
+        let sql = stmt.sql.clone();
+        let params = params(&stmt);
+
+        self.run(&sql, params, self.db.query_all(stmt), |rows| {
+            rows.len().try_into().unwrap_or(u64::MAX)
+        })
+        .await
+    }
+
+    fn is_mock_connection(&self) -> bool {
+        self.db.is_mock_connection()
+    }
+}
+
+/// Provide a Connection that wraps the shared DatabaseConnection pool with instrumentation, using
+/// the slow threshold and debug logging settings from the database Config. Services that inject the
+/// Connection get a tracing span for each statement.
+pub struct Provide<C: nakago_figment::Config> {
+    config_tag: Option<&'static Tag<C>>,
+    connection_tag: Option<&'static Tag<DatabaseConnection>>,
+}
+
+// Implement manually rather than deriving, to avoid requiring Default for the Config
+impl<C: nakago_figment::Config> Default for Provide<C> {
+    fn default() -> Self {
+        Self {
+            config_tag: None,
+            connection_tag: None,
+        }
+    }
+}
+
+impl<C: nakago_figment::Config> Provide<C> {
+    /// Create a new instance of Provide
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the config Tag for this instance
+    pub fn with_config_tag(self, config_tag: &'static Tag<C>) -> Self {
+        Self {
+            config_tag: Some(config_tag),
+            ..self
+        }
+    }
+
+    /// Set the DatabaseConnection Tag for this instance
+    pub fn with_connection_tag(self, connection_tag: &'static Tag<DatabaseConnection>) -> Self {
+        Self {
+            connection_tag: Some(connection_tag),
+            ..self
+        }
+    }
+}
+
+#[Provider]
+#[async_trait]
+impl<C: nakago_figment::Config> Provider<Connection> for Provide<C>
+where
+    Config: FromRef<C>,
+{
+    async fn provide(self: Arc<Self>, i: Inject) -> provider::Result<Arc<Connection>> {
+        let dep = if let Some(tag) = self.config_tag {
+            i.get_tag(tag).await?
+        } else {
+            i.get::<C>().await?
+        };
+
+        let config = Config::from_ref(&*dep);
+
+        let db = if let Some(tag) = self.connection_tag {
+            i.get_tag(tag).await?
+        } else {
+            i.get::<DatabaseConnection>().await?
+        };
+
+        let db = Instrumented::new(db, Options::from(&config));
+
+        Ok(Arc::new(db.into()))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::{
+        collections::BTreeMap,
+        io,
+        sync::{Mutex, PoisonError},
+    };
+
+    use pretty_assertions::assert_eq;
+    use sea_orm::Value;
+    use tracing_subscriber::{
+        filter::Targets,
+        fmt::{format::FmtSpan, MakeWriter},
+        layer::SubscriberExt,
+    };
+
+    use super::*;
+    use crate::{
+        config,
+        mock::{self, MockHandle},
+    };
+
+    #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, nakago_derive::Config)]
+    struct AppConfig {
+        database: Config,
+    }
+
+    impl Default for AppConfig {
+        fn default() -> Self {
+            Self {
+                database: config::test::config(),
+            }
+        }
+    }
+
+    // A log writer that collects the formatted output for assertions
+    #[derive(Clone, Default)]
+    struct Logs(Arc<Mutex<Vec<u8>>>);
+
+    impl Logs {
+        fn output(&self) -> String {
+            let buffer = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+
+            String::from_utf8_lossy(&buffer).to_string()
+        }
+    }
+
+    impl io::Write for Logs {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .extend_from_slice(buf);
+
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Logs {
+        type Writer = Logs;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    fn subscribe(logs: &Logs) -> tracing::subscriber::DefaultGuard {
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::TRACE)
+            .with_span_events(FmtSpan::CLOSE)
+            .with_ansi(false)
+            .with_writer(logs.clone())
+            .finish()
+            // SeaORM's own spans include the parameter values, so they're left out here
+            .with(Targets::new().with_target("nakago_sea_orm", tracing::Level::TRACE));
+
+        tracing::subscriber::set_default(subscriber)
+    }
+
+    fn select(db: &Instrumented) -> Statement {
+        Statement::from_sql_and_values(
+            db.get_database_backend(),
+            "SELECT * FROM users WHERE password = $1",
+            ["hunter2".into()],
+        )
+    }
+
+    fn select_one(db: &Connection) -> Statement {
+        Statement::from_string(db.get_database_backend(), "SELECT 1")
+    }
+
+    fn row() -> BTreeMap<&'static str, Value> {
+        BTreeMap::from([("id", Value::from(1))])
+    }
+
+    #[tokio::test]
+    async fn test_instrumented_slow_statement() -> anyhow::Result<()> {
+        let logs = Logs::default();
+        let _guard = subscribe(&logs);
+
+        let mock = MockHandle::new(DbBackend::Postgres);
+        mock.append_query_results([vec![row(), row()]]);
+
+        let db = Instrumented::new(
+            Arc::new(mock.connection()),
+            Options {
+                slow_threshold: Duration::ZERO,
+                log_level: None,
+            },
+        );
+
+        let rows = db.query_all(select(&db)).await?;
+        assert_eq!(2, rows.len());
+
+        let output = logs.output();
+
+        assert!(output.contains("WARN"));
+        assert!(output.contains("Slow database statement"));
+        assert!(output.contains("db.params=1"));
+        assert!(output.contains("db.rows=2"));
+        assert!(output.contains("histogram.db.statement.duration_ms="));
+        assert!(output.contains("db.operation=\"SELECT\""));
+        assert!(!output.contains("Database statement"));
+
+        // Parameter values are never recorded
+        assert!(!output.contains("hunter2"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_instrumented_debug_logging() -> anyhow::Result<()> {
+        let logs = Logs::default();
+        let _guard = subscribe(&logs);
+
+        let options = Options::from(&config::test::config());
+        assert_eq!(Duration::from_millis(250), options.slow_threshold);
+        assert_eq!(Some(Level::Debug), options.log_level);
+
+        let mock = MockHandle::new(DbBackend::Postgres);
+        mock.append_query_results([vec![row()]]);
+
+        let db = Instrumented::new(
+            Arc::new(mock.connection()),
+            Options {
+                slow_threshold: Duration::from_secs(3600),
+                ..options
+            },
+        );
+
+        // Instrumented Connections can be used anywhere a Connection is expected
+        let db: Connection = db.into();
+        assert!(db.query_one(select_one(&db)).await?.is_some());
+
+        let output = logs.output();
+
+        assert!(output.contains("DEBUG"));
+        assert!(output.contains("Database statement"));
+        assert!(output.contains("db.rows=1"));
+        assert!(!output.contains("Slow database statement"));
+
+        let options = Options::from(&Config {
+            debug: false,
+            slow_threshold_ms: None,
+            ..config::test::config()
+        });
+        assert_eq!(DEFAULT_SLOW_THRESHOLD, options.slow_threshold);
+        assert_eq!(None, options.log_level);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_operation() {
+        assert_eq!(
+            "SELECT",
+            operation(r#"SELECT "id" FROM "shows" WHERE "id" = $1"#)
+        );
+        assert_eq!("INSERT", operation("\n  insert into shows values ($1)"));
+        assert_eq!("DELETE", operation("Delete FROM shows"));
+        assert_eq!("OTHER", operation("CREATE TABLE shows (id TEXT)"));
+        assert_eq!("OTHER", operation(""));
+    }
+
+    #[tokio::test]
+    async fn test_provide() -> anyhow::Result<()> {
+        let logs = Logs::default();
+        let _guard = subscribe(&logs);
+
+        let i = Inject::default();
+        let mock = MockHandle::new(DbBackend::Postgres);
+        mock.append_query_results([vec![row()]]);
+
+        i.inject(AppConfig::default()).await?;
+        i.provide::<DatabaseConnection>(mock::Provide::new(mock.clone()))
+            .await?;
+        i.provide::<Connection>(Provide::<AppConfig>::new()).await?;
+
+        // The provided Connection wraps the DatabaseConnection with the Config settings
+        let db = i.get::<Connection>().await?;
+        let Connection::Instrumented(instrumented) = &*db else {
+            panic!("the provided Connection isn't Instrumented");
+        };
+        assert_eq!(
+            Duration::from_millis(250),
+            instrumented.options().slow_threshold
+        );
+
+        // A request Transaction keeps the instrumentation
+        let txn = Transaction::new(i.get::<DatabaseConnection>().await?);
+        let scoped = db.with_transaction(txn.clone());
+        assert!(matches!(scoped, Connection::Instrumented(_)));
+
+        assert!(scoped.query_one(select_one(&scoped)).await?.is_some());
+        assert!(txn.is_begun());
+
+        drop(scoped);
+        txn.finish().await?;
+
+        assert!(logs.output().contains("Database statement"));
+
+        Ok(())
+    }
+}
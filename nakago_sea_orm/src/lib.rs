@@ -7,8 +7,39 @@ pub mod connection;
 /// Database Config
 pub mod config;
 
-pub use config::{Config, DatabasePool};
-pub use connection::CONNECTION;
+/// Cursor pagination
+pub mod cursor;
+
+/// Statement tracing
+pub mod instrument;
+
+/// Scriptable Mock Databases
+pub mod mock;
+
+/// Read replica routing
+pub mod replicas;
+
+/// Generic CRUD Repositories
+pub mod repository;
+
+/// Request-scoped Transactions
+pub mod transaction;
+
+/// Testing
+#[cfg(feature = "sqlite")]
+pub mod test;
+
+/// Database Migrations
+#[cfg(feature = "migrate")]
+pub mod migrate;
+
+pub use config::{Config, DatabasePool, Routing};
+pub use connection::{Connection, CONNECTION};
+pub use instrument::Instrumented;
+pub use replicas::RoutingConnection;
+pub use repository::{Filter, Repository};
+pub use transaction::Transaction;
 
 // Re-exports
+pub use nakago_axum::utils::Ordering;
 pub use sea_orm::DatabaseConnection;
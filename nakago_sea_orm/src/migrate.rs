@@ -0,0 +1,364 @@
+use std::{collections::HashSet, fmt, marker::PhantomData, str::FromStr, sync::Arc};
+
+use nakago::{Error, Inject, Tag};
+use nakago_figment::FromRef;
+use sea_orm::{DatabaseConnection, DbErr};
+use sea_orm_migration::{MigrationStatus, MigratorTrait};
+
+use crate::Config;
+
+/// An error running or inspecting migrations
+#[derive(Debug, thiserror::Error)]
+pub enum MigrateError {
+    /// The database has migrations applied that this binary doesn't know about, which usually
+    /// means it was migrated by a newer release
+    #[error("the database is ahead of this binary, with unknown migrations applied: {}", .0.join(", "))]
+    Ahead(Vec<String>),
+
+    /// The migrator or the database failed
+    #[error(transparent)]
+    Database(#[from] DbErr),
+}
+
+/// The status of a single migration
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Status {
+    /// The migration name
+    pub name: String,
+
+    /// Whether the migration has been applied
+    pub applied: bool,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.applied { "Applied" } else { "Pending" };
+
+        write!(f, "{status:<8} {}", self.name)
+    }
+}
+
+/// Fail with `MigrateError::Ahead` if the database has migrations applied that the Migrator
+/// doesn't include. The migrations table is created if it doesn't exist yet.
+pub async fn check<M: MigratorTrait>(db: &DatabaseConnection) -> Result<(), MigrateError> {
+    let known: HashSet<String> = M::migrations()
+        .iter()
+        .map(|migration| migration.name().to_string())
+        .collect();
+
+    let unknown: Vec<String> = M::get_migration_models(db)
+        .await?
+        .into_iter()
+        .map(|model| model.version)
+        .filter(|version| !known.contains(version))
+        .collect();
+
+    if !unknown.is_empty() {
+        return Err(MigrateError::Ahead(unknown));
+    }
+
+    Ok(())
+}
+
+/// List each migration in the Migrator with whether it has been applied
+pub async fn status<M: MigratorTrait>(
+    db: &DatabaseConnection,
+) -> Result<Vec<Status>, MigrateError> {
+    check::<M>(db).await?;
+
+    let migrations = M::get_migration_with_status(db).await?;
+
+    Ok(migrations
+        .iter()
+        .map(|migration| Status {
+            name: migration.name().to_string(),
+            applied: matches!(migration.status(), MigrationStatus::Applied),
+        })
+        .collect())
+}
+
+/// Apply pending migrations, up to the given number of steps or all of them if None
+pub async fn up<M: MigratorTrait>(
+    db: &DatabaseConnection,
+    steps: Option<u32>,
+) -> Result<(), MigrateError> {
+    check::<M>(db).await?;
+
+    Ok(M::up(db, steps).await?)
+}
+
+/// Roll back applied migrations, up to the given number of steps or all of them if None
+pub async fn down<M: MigratorTrait>(
+    db: &DatabaseConnection,
+    steps: Option<u32>,
+) -> Result<(), MigrateError> {
+    check::<M>(db).await?;
+
+    Ok(M::down(db, steps).await?)
+}
+
+/// Drop every table in the database and apply all migrations from scratch
+pub async fn fresh<M: MigratorTrait>(db: &DatabaseConnection) -> Result<(), MigrateError> {
+    Ok(M::fresh(db).await?)
+}
+
+/// A migration operation, for use from a CLI subcommand or an admin endpoint
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// List the migrations and their status
+    Status,
+
+    /// Apply pending migrations
+    Up(Option<u32>),
+
+    /// Roll back applied migrations
+    Down(Option<u32>),
+
+    /// Drop every table and apply all migrations
+    Fresh,
+}
+
+impl Command {
+    /// Run the operation, returning the migration statuses afterwards
+    pub async fn run<M: MigratorTrait>(
+        &self,
+        db: &DatabaseConnection,
+    ) -> Result<Vec<Status>, MigrateError> {
+        match self {
+            Command::Status => {}
+            Command::Up(steps) => up::<M>(db, *steps).await?,
+            Command::Down(steps) => down::<M>(db, *steps).await?,
+            Command::Fresh => fresh::<M>(db).await?,
+        }
+
+        status::<M>(db).await
+    }
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    /// Parse a command such as "status", "up", "up 2", "down 1" or "fresh"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+
+        let command = parts.next().unwrap_or_default();
+
+        let steps = parts
+            .next()
+            .map(|steps| {
+                steps
+                    .parse::<u32>()
+                    .map_err(|err| format!("invalid number of steps {steps:?}: {err}"))
+            })
+            .transpose()?;
+
+        if let Some(extra) = parts.next() {
+            return Err(format!("unexpected argument {extra:?}"));
+        }
+
+        match (command, steps) {
+            ("status", None) => Ok(Command::Status),
+            ("up", steps) => Ok(Command::Up(steps)),
+            ("down", steps) => Ok(Command::Down(steps)),
+            ("fresh", None) => Ok(Command::Fresh),
+            ("status" | "fresh", Some(_)) => {
+                Err(format!("{command} doesn't take a number of steps"))
+            }
+            _ => Err(format!("unknown migration command {command:?}")),
+        }
+    }
+}
+
+/// Check the database migrations at startup, applying any pending ones when the `migrate` flag is
+/// set in the database Config. Startup fails if the database is ahead of the Migrator.
+pub struct Init<M: MigratorTrait, C: nakago_figment::Config> {
+    config_tag: Option<&'static Tag<C>>,
+    connection_tag: Option<&'static Tag<DatabaseConnection>>,
+    _phantom: PhantomData<fn() -> M>,
+}
+
+// Implement manually rather than deriving, to avoid requiring Default for the Migrator
+impl<M: MigratorTrait, C: nakago_figment::Config> Default for Init<M, C> {
+    fn default() -> Self {
+        Self {
+            config_tag: None,
+            connection_tag: None,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<M: MigratorTrait, C: nakago_figment::Config> Init<M, C>
+where
+    Config: FromRef<C>,
+{
+    /// Set the config Tag for this instance
+    pub fn with_config_tag(self, config_tag: &'static Tag<C>) -> Self {
+        Self {
+            config_tag: Some(config_tag),
+            ..self
+        }
+    }
+
+    /// Set the DatabaseConnection Tag for this instance
+    pub fn with_connection_tag(self, connection_tag: &'static Tag<DatabaseConnection>) -> Self {
+        Self {
+            connection_tag: Some(connection_tag),
+            ..self
+        }
+    }
+
+    /// Check the migrations, and apply pending ones if the Config says so
+    pub async fn init(&self, i: &Inject) -> nakago::Result<()> {
+        let dep = if let Some(tag) = self.config_tag {
+            i.get_tag(tag).await?
+        } else {
+            i.get::<C>().await?
+        };
+
+        let config = Config::from_ref(&*dep);
+
+        let db = if let Some(tag) = self.connection_tag {
+            i.get_tag(tag).await?
+        } else {
+            i.get::<DatabaseConnection>().await?
+        };
+
+        let result = if config.migrate {
+            up::<M>(&db, None).await
+        } else {
+            check::<M>(&db).await
+        };
+
+        result.map_err(|err| Error::Any(Arc::new(err.into())))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use async_trait::async_trait;
+    use pretty_assertions::assert_eq;
+    use sea_orm::sea_query::{Alias, ColumnDef, Table};
+    use sea_orm_migration::{MigrationName, MigrationTrait, SchemaManager};
+
+    use super::*;
+
+    pub(crate) struct CreateShows;
+
+    impl MigrationName for CreateShows {
+        fn name(&self) -> &str {
+            "m20240101_000001_create_shows"
+        }
+    }
+
+    #[async_trait]
+    impl MigrationTrait for CreateShows {
+        async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+            manager
+                .create_table(
+                    Table::create()
+                        .table(Alias::new("shows"))
+                        .col(
+                            ColumnDef::new(Alias::new("id"))
+                                .string()
+                                .not_null()
+                                .primary_key(),
+                        )
+                        .col(ColumnDef::new(Alias::new("title")).string().not_null())
+                        .to_owned(),
+                )
+                .await
+        }
+
+        async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+            manager
+                .drop_table(Table::drop().table(Alias::new("shows")).to_owned())
+                .await
+        }
+    }
+
+    /// A Migrator with a single migration that creates a `shows (id, title)` table
+    pub(crate) struct Migrator;
+
+    #[async_trait]
+    impl MigratorTrait for Migrator {
+        fn migrations() -> Vec<Box<dyn MigrationTrait>> {
+            vec![Box::new(CreateShows)]
+        }
+    }
+
+    #[test]
+    fn test_command_from_str() {
+        assert_eq!(Ok(Command::Status), "status".parse());
+        assert_eq!(Ok(Command::Up(None)), "up".parse());
+        assert_eq!(Ok(Command::Up(Some(2))), " up  2 ".parse());
+        assert_eq!(Ok(Command::Down(Some(1))), "down 1".parse());
+        assert_eq!(Ok(Command::Fresh), "fresh".parse());
+
+        assert!("status 1".parse::<Command>().is_err());
+        assert!("fresh 1".parse::<Command>().is_err());
+        assert!("up 2 3".parse::<Command>().is_err());
+        assert!("up two".parse::<Command>().is_err());
+        assert!("down -1".parse::<Command>().is_err());
+        assert!("sideways".parse::<Command>().is_err());
+        assert!("".parse::<Command>().is_err());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_migrate_commands() -> anyhow::Result<()> {
+        let test_db = crate::test::TestDatabase::memory().await?;
+        let db = test_db.connection();
+
+        let statuses = Command::Up(None).run::<Migrator>(&db).await?;
+        assert_eq!(
+            vec![Status {
+                name: "m20240101_000001_create_shows".to_string(),
+                applied: true,
+            }],
+            statuses
+        );
+
+        let statuses = Command::Down(Some(1)).run::<Migrator>(&db).await?;
+        assert!(!statuses[0].applied);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_check_ahead() -> anyhow::Result<()> {
+        let test_db = crate::test::TestDatabase::memory().await?;
+        let db = test_db.connection();
+
+        up::<Migrator>(&db, None).await?;
+        check::<Migrator>(&db).await?;
+
+        // A migration applied by a newer release
+        test_db
+            .execute(
+                "INSERT INTO seaql_migrations (version, applied_at) \
+                 VALUES ('m20990101_000001_from_the_future', 0)",
+            )
+            .await?;
+
+        let result = check::<Migrator>(&db).await;
+        assert!(matches!(
+            result,
+            Err(MigrateError::Ahead(unknown)) if unknown == ["m20990101_000001_from_the_future"]
+        ));
+
+        // Nothing else runs against a database that is ahead
+        assert!(matches!(
+            up::<Migrator>(&db, None).await,
+            Err(MigrateError::Ahead(_))
+        ));
+        assert!(matches!(
+            status::<Migrator>(&db).await,
+            Err(MigrateError::Ahead(_))
+        ));
+
+        Ok(())
+    }
+}
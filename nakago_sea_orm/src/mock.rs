@@ -0,0 +1,353 @@
+use std::{
+    fmt::Write as _,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use async_trait::async_trait;
+use nakago::{provider, Inject, Provider};
+use nakago_derive::Provider;
+use sea_orm::{
+    DatabaseConnection, DbBackend, DbErr, ExecResult, IntoMockRow, MockDatabase,
+    MockDatabaseConnection, MockDatabaseTrait, MockExecResult, QueryResult, Statement, Transaction,
+};
+
+/// A handle to a live MockDatabase, shared by the test and every DatabaseConnection created from
+/// it. Results can be appended between calls, and the SQL that was sent can be inspected at any
+/// point, without rebuilding the container.
+#[derive(Clone, Debug)]
+pub struct MockHandle(Arc<Mutex<State>>);
+
+#[derive(Debug)]
+struct State {
+    db: MockDatabase,
+    exec_counter: usize,
+    query_counter: usize,
+    log: Vec<Transaction>,
+}
+
+impl State {
+    // Move any finished transactions from the MockDatabase into the log
+    fn collect(&mut self) {
+        let drained = self.db.drain_transaction_log();
+
+        self.log.extend(drained);
+    }
+}
+
+impl MockHandle {
+    /// Create a new MockHandle for the given backend
+    pub fn new(backend: DbBackend) -> Self {
+        Self(Arc::new(Mutex::new(State {
+            db: MockDatabase::new(backend),
+            exec_counter: 0,
+            query_counter: 0,
+            log: Vec::new(),
+        })))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // Apply a MockDatabase builder method to the live MockDatabase
+    fn update(&self, f: impl FnOnce(MockDatabase) -> MockDatabase) -> &Self {
+        let mut state = self.lock();
+
+        let backend = state.db.get_database_backend();
+        let db = std::mem::replace(&mut state.db, MockDatabase::new(backend));
+        state.db = f(db);
+
+        self
+    }
+
+    /// Create a DatabaseConnection backed by the shared MockDatabase
+    pub fn connection(&self) -> DatabaseConnection {
+        DatabaseConnection::MockDatabaseConnection(Arc::new(MockDatabaseConnection::new(Shared(
+            self.clone(),
+        ))))
+    }
+
+    /// Queue results for upcoming queries, one set of rows per query
+    pub fn append_query_results<T, I, II>(&self, results: II) -> &Self
+    where
+        T: IntoMockRow,
+        I: IntoIterator<Item = T>,
+        II: IntoIterator<Item = I>,
+    {
+        self.update(|db| db.append_query_results(results))
+    }
+
+    /// Queue results for upcoming statements passed to `execute`
+    pub fn append_exec_results<I>(&self, results: I) -> &Self
+    where
+        I: IntoIterator<Item = MockExecResult>,
+    {
+        self.update(|db| db.append_exec_results(results))
+    }
+
+    /// Queue errors for upcoming queries
+    pub fn append_query_errors<I>(&self, errors: I) -> &Self
+    where
+        I: IntoIterator<Item = DbErr>,
+    {
+        self.update(|db| db.append_query_errors(errors))
+    }
+
+    /// Queue errors for upcoming statements passed to `execute`
+    pub fn append_exec_errors<I>(&self, errors: I) -> &Self
+    where
+        I: IntoIterator<Item = DbErr>,
+    {
+        self.update(|db| db.append_exec_errors(errors))
+    }
+
+    /// The transactions sent so far, in order. Each statement outside of a transaction is logged
+    /// as a transaction of its own.
+    pub fn transaction_log(&self) -> Vec<Transaction> {
+        let mut state = self.lock();
+        state.collect();
+
+        state.log.clone()
+    }
+
+    /// The SQL of every statement sent so far, in order
+    pub fn statements(&self) -> Vec<String> {
+        self.transaction_log()
+            .iter()
+            .flat_map(Transaction::statements)
+            .map(|stmt| stmt.sql.clone())
+            .collect()
+    }
+
+    /// Clear the transaction log, so that later assertions only cover what is sent afterwards
+    pub fn clear_log(&self) {
+        let mut state = self.lock();
+        state.collect();
+
+        state.log.clear();
+    }
+
+    /// Assert that the given transactions were sent, in any order, and nothing else
+    ///
+    /// # Panics
+    ///
+    /// Panics with the missing and unexpected transactions if they don't match.
+    pub fn assert_transactions(&self, expected: Vec<Transaction>) {
+        let (missing, unexpected) = difference(expected, self.transaction_log());
+
+        assert_matched(&missing, &unexpected);
+    }
+
+    /// Assert that statements with the given SQL were sent, in any order, and nothing else
+    ///
+    /// # Panics
+    ///
+    /// Panics with the missing and unexpected statements if they don't match.
+    pub fn assert_statements(&self, expected: &[&str]) {
+        let expected = expected.iter().map(ToString::to_string).collect();
+        let (missing, unexpected) = difference(expected, self.statements());
+
+        assert_matched(&missing, &unexpected);
+    }
+}
+
+// The expected items that weren't found, and the actual items that weren't expected
+fn difference<T: PartialEq>(expected: Vec<T>, mut actual: Vec<T>) -> (Vec<T>, Vec<T>) {
+    let mut missing = Vec::new();
+
+    for item in expected {
+        match actual.iter().position(|candidate| *candidate == item) {
+            Some(index) => {
+                actual.remove(index);
+            }
+            None => missing.push(item),
+        }
+    }
+
+    (missing, actual)
+}
+
+fn assert_matched<T: std::fmt::Debug>(missing: &[T], unexpected: &[T]) {
+    if missing.is_empty() && unexpected.is_empty() {
+        return;
+    }
+
+    let mut message = String::from("the mock database statements didn't match\n");
+
+    for item in missing {
+        let _ = writeln!(message, "  missing: {item:?}");
+    }
+
+    for item in unexpected {
+        let _ = writeln!(message, "  unexpected: {item:?}");
+    }
+
+    panic!("{message}");
+}
+
+// The MockDatabaseTrait implementation behind each connection. Each connection counts calls
+// separately, so the shared counters are used instead to find the next queued result.
+#[derive(Debug)]
+struct Shared(MockHandle);
+
+impl MockDatabaseTrait for Shared {
+    fn execute(&mut self, _counter: usize, stmt: Statement) -> Result<ExecResult, DbErr> {
+        let mut state = self.0.lock();
+
+        let counter = state.exec_counter;
+        state.exec_counter += 1;
+
+        state.db.execute(counter, stmt)
+    }
+
+    fn query(&mut self, _counter: usize, stmt: Statement) -> Result<Vec<QueryResult>, DbErr> {
+        let mut state = self.0.lock();
+
+        let counter = state.query_counter;
+        state.query_counter += 1;
+
+        state.db.query(counter, stmt)
+    }
+
+    fn begin(&mut self) {
+        self.0.lock().db.begin();
+    }
+
+    fn commit(&mut self) {
+        self.0.lock().db.commit();
+    }
+
+    fn rollback(&mut self) {
+        self.0.lock().db.rollback();
+    }
+
+    fn drain_transaction_log(&mut self) -> Vec<Transaction> {
+        let mut state = self.0.lock();
+        state.collect();
+
+        std::mem::take(&mut state.log)
+    }
+
+    fn get_database_backend(&self) -> DbBackend {
+        self.0.lock().db.get_database_backend()
+    }
+
+    fn ping(&self) -> Result<(), DbErr> {
+        self.0.lock().db.ping()
+    }
+}
+
+/// Provide a Mock Database Connection backed by a shared MockHandle, so that a test can keep
+/// scripting results and inspecting statements after the connection is provided
+pub struct Provide {
+    handle: MockHandle,
+}
+
+impl Provide {
+    /// Create a new instance of Provide for the given MockHandle
+    pub fn new(handle: MockHandle) -> Self {
+        Self { handle }
+    }
+}
+
+#[Provider]
+#[async_trait]
+impl Provider<DatabaseConnection> for Provide {
+    async fn provide(self: Arc<Self>, _i: Inject) -> provider::Result<Arc<DatabaseConnection>> {
+        Ok(Arc::new(self.handle.connection()))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::collections::BTreeMap;
+
+    use pretty_assertions::assert_eq;
+    use sea_orm::{ConnectionTrait, Value};
+
+    use super::*;
+
+    fn row(name: &str) -> BTreeMap<&'static str, Value> {
+        BTreeMap::from([("name", Value::from(name))])
+    }
+
+    fn select(db: &DatabaseConnection, sql: &str) -> Statement {
+        Statement::from_string(db.get_database_backend(), sql)
+    }
+
+    #[tokio::test]
+    async fn test_mock_handle_scripting() -> anyhow::Result<()> {
+        let i = Inject::default();
+        let mock = MockHandle::new(DbBackend::Postgres);
+
+        i.provide::<DatabaseConnection>(Provide::new(mock.clone()))
+            .await?;
+
+        mock.append_query_results([vec![row("first")]]);
+
+        let db = i.get::<DatabaseConnection>().await?;
+        let first = db.query_one(select(&db, "SELECT 1")).await?;
+        assert!(first.is_some());
+
+        // Results can be queued after the connection is in use
+        mock.append_query_results([vec![row("second")], vec![]])
+            .append_exec_results([MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }]);
+
+        let second = db.query_all(select(&db, "SELECT 2")).await?;
+        let name: String = second[0].try_get("", "name")?;
+        assert_eq!("second", name);
+
+        assert!(db.query_one(select(&db, "SELECT 3")).await?.is_none());
+        assert_eq!(
+            1,
+            db.execute(select(&db, "DELETE FROM shows"))
+                .await?
+                .rows_affected()
+        );
+
+        // Nothing else is queued
+        assert!(db.query_one(select(&db, "SELECT 4")).await.is_err());
+
+        assert_eq!(
+            vec![
+                "SELECT 1",
+                "SELECT 2",
+                "SELECT 3",
+                "DELETE FROM shows",
+                "SELECT 4"
+            ],
+            mock.statements()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mock_handle_assertions() -> anyhow::Result<()> {
+        let mock = MockHandle::new(DbBackend::Postgres);
+        mock.append_exec_results([MockExecResult::default(), MockExecResult::default()]);
+
+        let db = mock.connection();
+        db.execute(select(&db, "UPDATE shows SET title = 'a'"))
+            .await?;
+        db.execute(select(&db, "DELETE FROM episodes")).await?;
+
+        // Matching ignores the order the statements were sent in
+        mock.assert_statements(&["DELETE FROM episodes", "UPDATE shows SET title = 'a'"]);
+        mock.assert_transactions(vec![
+            Transaction::one(select(&db, "DELETE FROM episodes")),
+            Transaction::one(select(&db, "UPDATE shows SET title = 'a'")),
+        ]);
+
+        let result = std::panic::catch_unwind(|| mock.assert_statements(&["DELETE FROM shows"]));
+        assert!(result.is_err());
+
+        mock.clear_log();
+        assert!(mock.statements().is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,582 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use nakago::{provider, to_provider_error, Inject, Provider, Tag};
+use nakago_derive::Provider;
+use nakago_figment::FromRef;
+use sea_orm::{
+    AccessMode, ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbBackend, DbErr,
+    ExecResult, IsolationLevel, QueryResult, Statement, TransactionError, TransactionTrait,
+};
+
+use crate::{config::Routing, Config};
+
+/// How long a replica is skipped after a connection failure, unless configured otherwise
+pub const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// A read replica, with the state used to route queries to it
+#[derive(Debug)]
+struct Replica {
+    db: DatabaseConnection,
+    in_flight: AtomicUsize,
+    failed_at: Mutex<Option<Instant>>,
+}
+
+impl Replica {
+    fn is_healthy(&self, retry_after: Duration) -> bool {
+        let failed_at = self
+            .failed_at
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        failed_at.is_none_or(|failed_at| failed_at.elapsed() >= retry_after)
+    }
+
+    fn set_healthy(&self, healthy: bool) {
+        let mut failed_at = self
+            .failed_at
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        *failed_at = if healthy { None } else { Some(Instant::now()) };
+    }
+}
+
+// Counts a query against a replica for as long as it is running
+struct InFlight<'a>(&'a AtomicUsize);
+
+impl<'a> InFlight<'a> {
+    fn start(count: &'a AtomicUsize) -> Self {
+        count.fetch_add(1, Ordering::SeqCst);
+
+        Self(count)
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// A database connection that routes reads to a replica and everything else to the primary.
+///
+/// Plain SELECT statements, which back `find`, `paginate` and friends, go to a healthy replica
+/// chosen by the `Routing` strategy. Every other statement goes to the primary, including writes
+/// sent through `query_one` or `query_all` for a RETURNING clause, such as `ActiveModel::insert` on
+/// Postgres, and SELECTs with a locking clause such as FOR UPDATE. Every Transaction uses the
+/// primary too, so reads within a Transaction see its writes. A replica that fails to
+/// connect is skipped until `retry_after` has passed, and the read falls back to the primary.
+///
+/// Replicas may lag behind the primary. Use `primary()` for reads that must see a recent write.
+#[derive(Debug)]
+pub struct RoutingConnection {
+    primary: Arc<DatabaseConnection>,
+    replicas: Vec<Replica>,
+    routing: Routing,
+    retry_after: Duration,
+    next: AtomicUsize,
+}
+
+impl RoutingConnection {
+    /// Create a new RoutingConnection with the given primary and replicas
+    pub fn new(
+        primary: Arc<DatabaseConnection>,
+        replicas: Vec<DatabaseConnection>,
+        routing: Routing,
+    ) -> Self {
+        Self {
+            primary,
+            replicas: replicas
+                .into_iter()
+                .map(|db| Replica {
+                    db,
+                    in_flight: AtomicUsize::new(0),
+                    failed_at: Mutex::new(None),
+                })
+                .collect(),
+            routing,
+            retry_after: DEFAULT_RETRY_AFTER,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Set how long a replica is skipped after a connection failure
+    pub fn with_retry_after(self, retry_after: Duration) -> Self {
+        Self {
+            retry_after,
+            ..self
+        }
+    }
+
+    /// The primary connection
+    pub fn primary(&self) -> &DatabaseConnection {
+        &self.primary
+    }
+
+    /// Ping each replica, marking it healthy or unhealthy, and return the number that are healthy
+    pub async fn check_health(&self) -> usize {
+        let mut healthy = 0;
+
+        for replica in &self.replicas {
+            let ok = replica.db.ping().await.is_ok();
+
+            replica.set_healthy(ok);
+
+            if ok {
+                healthy += 1;
+            }
+        }
+
+        healthy
+    }
+
+    // Choose a healthy replica, or None if there are none
+    fn choose(&self) -> Option<&Replica> {
+        let count = self.replicas.len();
+        if count == 0 {
+            return None;
+        }
+
+        // Start from the next replica in turn, so that ties are spread evenly
+        let start = self.next.fetch_add(1, Ordering::Relaxed) % count;
+
+        let mut healthy = (0..count)
+            .map(|offset| &self.replicas[(start + offset) % count])
+            .filter(|replica| replica.is_healthy(self.retry_after));
+
+        match self.routing {
+            Routing::RoundRobin => healthy.next(),
+            Routing::LeastConnections => {
+                healthy.min_by_key(|replica| replica.in_flight.load(Ordering::SeqCst))
+            }
+        }
+    }
+
+    // Run a read against a replica, falling back to the primary if none are available or the
+    // chosen replica can't be reached
+    async fn read<'a, T, F, Fut>(&'a self, query: F) -> Result<T, DbErr>
+    where
+        F: Fn(&'a DatabaseConnection) -> Fut,
+        Fut: Future<Output = Result<T, DbErr>>,
+    {
+        if let Some(replica) = self.choose() {
+            let result = {
+                let _in_flight = InFlight::start(&replica.in_flight);
+
+                query(&replica.db).await
+            };
+
+            match result {
+                Err(DbErr::Conn(err)) => {
+                    log::warn!("Read replica is unavailable, using the primary: {err}");
+
+                    replica.set_healthy(false);
+                }
+                Err(DbErr::ConnectionAcquire(err)) => {
+                    log::warn!("Read replica is unavailable, using the primary: {err}");
+
+                    replica.set_healthy(false);
+                }
+                result => return result,
+            }
+        }
+
+        query(&self.primary).await
+    }
+}
+
+// Whether a statement can be sent to a replica: a plain SELECT without a RETURNING, INTO or
+// locking clause. Anything that can't be classified goes to the primary.
+fn is_read(sql: &str) -> bool {
+    let words = sql
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>();
+
+    if words.first().map(String::as_str) != Some("select") {
+        return false;
+    }
+
+    let locking = words.windows(2).any(|pair| {
+        matches!(
+            (pair[0].as_str(), pair[1].as_str()),
+            ("for", "update" | "share" | "no" | "key") | ("lock", "in")
+        )
+    });
+
+    !locking
+        && !words
+            .iter()
+            .any(|word| word == "returning" || word == "into")
+}
+
+#[async_trait]
+impl ConnectionTrait for RoutingConnection {
+    fn get_database_backend(&self) -> DbBackend {
+        self.primary.get_database_backend()
+    }
+
+    async fn execute(&self, stmt: Statement) -> Result<ExecResult, DbErr> {
+        self.primary.execute(stmt).await
+    }
+
+    async fn execute_unprepared(&self, sql: &str) -> Result<ExecResult, DbErr> {
+        self.primary.execute_unprepared(sql).await
+    }
+
+    async fn query_one(&self, stmt: Statement) -> Result<Option<QueryResult>, DbErr> {
+        if !is_read(&stmt.sql) {
+            return self.primary.query_one(stmt).await;
+        }
+
+        self.read(|db| db.query_one(stmt.clone())).await
+    }
+
+    async fn query_all(&self, stmt: Statement) -> Result<Vec<QueryResult>, DbErr> {
+        if !is_read(&stmt.sql) {
+            return self.primary.query_all(stmt).await;
+        }
+
+        self.read(|db| db.query_all(stmt.clone())).await
+    }
+
+    fn is_mock_connection(&self) -> bool {
+        self.primary.is_mock_connection()
+    }
+}
+
+#[async_trait]
+impl TransactionTrait for RoutingConnection {
+    async fn begin(&self) -> Result<DatabaseTransaction, DbErr> {
+        self.primary.begin().await
+    }
+
+    async fn begin_with_config(
+        &self,
+        isolation_level: Option<IsolationLevel>,
+        access_mode: Option<AccessMode>,
+    ) -> Result<DatabaseTransaction, DbErr> {
+        self.primary
+            .begin_with_config(isolation_level, access_mode)
+            .await
+    }
+
+    async fn transaction<F, T, E>(&self, callback: F) -> Result<T, TransactionError<E>>
+    where
+        F: for<'c> FnOnce(
+                &'c DatabaseTransaction,
+            ) -> Pin<Box<dyn Future<Output = Result<T, E>> + Send + 'c>>
+            + Send,
+        T: Send,
+        E: std::fmt::Display + std::fmt::Debug + Send,
+    {
+        self.primary.transaction(callback).await
+    }
+
+    async fn transaction_with_config<F, T, E>(
+        &self,
+        callback: F,
+        isolation_level: Option<IsolationLevel>,
+        access_mode: Option<AccessMode>,
+    ) -> Result<T, TransactionError<E>>
+    where
+        F: for<'c> FnOnce(
+                &'c DatabaseTransaction,
+            ) -> Pin<Box<dyn Future<Output = Result<T, E>> + Send + 'c>>
+            + Send,
+        T: Send,
+        E: std::fmt::Display + std::fmt::Debug + Send,
+    {
+        self.primary
+            .transaction_with_config(callback, isolation_level, access_mode)
+            .await
+    }
+}
+
+/// Provide a RoutingConnection for the `replicas` in the database Config, using the
+/// DatabaseConnection in the container as the primary. Replicas are connected lazily, so an
+/// unavailable replica doesn't prevent startup.
+pub struct Provide<C: nakago_figment::Config> {
+    config_tag: Option<&'static Tag<C>>,
+    connection_tag: Option<&'static Tag<DatabaseConnection>>,
+}
+
+// Implement manually rather than deriving, to avoid requiring Default for the Config
+impl<C: nakago_figment::Config> Default for Provide<C> {
+    fn default() -> Self {
+        Self {
+            config_tag: None,
+            connection_tag: None,
+        }
+    }
+}
+
+impl<C: nakago_figment::Config> Provide<C> {
+    /// Create a new instance of Provide
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the config Tag for this instance
+    pub fn with_config_tag(self, config_tag: &'static Tag<C>) -> Self {
+        Self {
+            config_tag: Some(config_tag),
+            ..self
+        }
+    }
+
+    /// Set the primary DatabaseConnection Tag for this instance
+    pub fn with_connection_tag(self, connection_tag: &'static Tag<DatabaseConnection>) -> Self {
+        Self {
+            connection_tag: Some(connection_tag),
+            ..self
+        }
+    }
+}
+
+#[Provider]
+#[async_trait]
+impl<C: nakago_figment::Config> Provider<RoutingConnection> for Provide<C>
+where
+    Config: FromRef<C>,
+{
+    async fn provide(self: Arc<Self>, i: Inject) -> provider::Result<Arc<RoutingConnection>> {
+        let dep = if let Some(tag) = self.config_tag {
+            i.get_tag(tag).await?
+        } else {
+            i.get::<C>().await?
+        };
+
+        let config = Config::from_ref(&*dep);
+
+        let primary = if let Some(tag) = self.connection_tag {
+            i.get_tag(tag).await?
+        } else {
+            i.get::<DatabaseConnection>().await?
+        };
+
+        let mut replicas = Vec::with_capacity(config.replicas.len());
+
+        for url in &config.replicas {
+            let mut options = Config {
+                url: url.clone(),
+                ..config.clone()
+            }
+            .connect_options();
+
+            options.connect_lazy(true);
+
+            replicas.push(
+                sea_orm::Database::connect(options)
+                    .await
+                    .map_err(to_provider_error)?,
+            );
+        }
+
+        Ok(Arc::new(RoutingConnection::new(
+            primary,
+            replicas,
+            config.routing,
+        )))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+    use sea_orm::{ActiveModelTrait, ConnectOptions, Database, EntityTrait, Set};
+
+    use super::*;
+    use crate::{mock::MockHandle, repository::test::shows};
+
+    fn temp_db() -> PathBuf {
+        std::env::temp_dir().join(format!("nakago-replicas-{}.db", ulid::Ulid::new()))
+    }
+
+    // Create a database that identifies itself by name
+    async fn named_db(name: &str) -> anyhow::Result<DatabaseConnection> {
+        let db = Database::connect(format!("sqlite://{}?mode=rwc", temp_db().display())).await?;
+
+        db.execute_unprepared("CREATE TABLE source (name TEXT NOT NULL)")
+            .await?;
+        db.execute_unprepared(&format!("INSERT INTO source (name) VALUES ('{name}')"))
+            .await?;
+
+        Ok(db)
+    }
+
+    async fn source<C: ConnectionTrait>(db: &C) -> anyhow::Result<Vec<String>> {
+        let rows = db
+            .query_all(Statement::from_string(
+                db.get_database_backend(),
+                "SELECT name FROM source ORDER BY name",
+            ))
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| row.try_get("", "name"))
+            .collect::<Result<_, _>>()?)
+    }
+
+    async fn routing(replicas: &[&str], routing: Routing) -> anyhow::Result<RoutingConnection> {
+        let primary = Arc::new(named_db("primary").await?);
+
+        let mut dbs = Vec::new();
+        for name in replicas {
+            dbs.push(named_db(name).await?);
+        }
+
+        Ok(RoutingConnection::new(primary, dbs, routing))
+    }
+
+    #[tokio::test]
+    async fn test_reads_round_robin() -> anyhow::Result<()> {
+        let db = routing(&["first", "second"], Routing::RoundRobin).await?;
+
+        assert_eq!(vec!["first"], source(&db).await?);
+        assert_eq!(vec!["second"], source(&db).await?);
+        assert_eq!(vec!["first"], source(&db).await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reads_least_connections() -> anyhow::Result<()> {
+        let db = routing(&["first", "second"], Routing::LeastConnections).await?;
+
+        // The first replica is busy, so every read goes to the second
+        let _busy = InFlight::start(&db.replicas[0].in_flight);
+
+        assert_eq!(vec!["second"], source(&db).await?);
+        assert_eq!(vec!["second"], source(&db).await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_writes_and_transactions_use_the_primary() -> anyhow::Result<()> {
+        let db = routing(&["replica"], Routing::RoundRobin).await?;
+
+        db.execute_unprepared("INSERT INTO source (name) VALUES ('written')")
+            .await?;
+
+        assert_eq!(vec!["replica"], source(&db).await?);
+        assert_eq!(vec!["primary", "written"], source(db.primary()).await?);
+
+        let txn = db.begin().await?;
+        assert_eq!(vec!["primary", "written"], source(&txn).await?);
+        txn.rollback().await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unhealthy_replica_falls_back() -> anyhow::Result<()> {
+        let primary = Arc::new(named_db("primary").await?);
+
+        // A replica that can't be opened
+        let missing = temp_db().join("missing.db");
+        let mut options = ConnectOptions::new(format!("sqlite://{}?mode=ro", missing.display()));
+        options
+            .connect_lazy(true)
+            .acquire_timeout(Duration::from_secs(1));
+
+        let db = RoutingConnection::new(
+            primary,
+            vec![Database::connect(options).await?],
+            Routing::RoundRobin,
+        );
+
+        assert_eq!(vec!["primary"], source(&db).await?);
+        assert!(!db.replicas[0].is_healthy(db.retry_after));
+
+        // Still unhealthy after a check, so reads keep going to the primary
+        assert_eq!(0, db.check_health().await);
+        assert_eq!(vec!["primary"], source(&db).await?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_read() {
+        assert!(is_read(
+            r#"SELECT "shows"."id" FROM "shows" WHERE "shows"."id" = $1"#
+        ));
+        assert!(is_read("  select name from source order by name"));
+        assert!(is_read(r#"SELECT "returning_user" FROM "updates""#));
+
+        assert!(!is_read(
+            r#"INSERT INTO "shows" ("id") VALUES ($1) RETURNING "id""#
+        ));
+        assert!(!is_read(
+            r#"UPDATE "shows" SET "title" = $1 WHERE "id" = $2 RETURNING "id""#
+        ));
+        assert!(!is_read(r#"DELETE FROM "shows" RETURNING "id""#));
+        assert!(!is_read(r#"SELECT "id" FROM "shows" FOR UPDATE"#));
+        assert!(!is_read(
+            r#"SELECT "id" FROM "shows" FOR NO KEY UPDATE SKIP LOCKED"#
+        ));
+        assert!(!is_read("SELECT id FROM shows FOR SHARE"));
+        assert!(!is_read("SELECT id FROM shows LOCK IN SHARE MODE"));
+        assert!(!is_read("SELECT * INTO archived_shows FROM shows"));
+        assert!(!is_read(
+            "WITH deleted AS (DELETE FROM shows RETURNING id) SELECT * FROM deleted"
+        ));
+        assert!(!is_read(""));
+    }
+
+    #[tokio::test]
+    async fn test_returning_writes_use_the_primary() -> anyhow::Result<()> {
+        let primary = MockHandle::new(DbBackend::Postgres);
+        let replica = MockHandle::new(DbBackend::Postgres);
+
+        let db = RoutingConnection::new(
+            Arc::new(primary.connection()),
+            vec![replica.connection()],
+            Routing::RoundRobin,
+        );
+
+        let show = shows::Model {
+            id: "1".to_string(),
+            title: "Show".to_string(),
+        };
+
+        primary.append_query_results([vec![show.clone()]]);
+        replica.append_query_results([vec![show.clone()]]);
+
+        // Postgres sends INSERT ... RETURNING through query_one
+        let created = shows::ActiveModel {
+            id: Set("1".to_string()),
+            title: Set("Show".to_string()),
+        }
+        .insert(&db)
+        .await?;
+
+        assert_eq!(show, created);
+
+        let found = shows::Entity::find_by_id("1").one(&db).await?;
+        assert_eq!(Some(show), found);
+
+        primary.assert_statements(&[
+            r#"INSERT INTO "shows" ("id", "title") VALUES ($1, $2) RETURNING "id", "title""#,
+        ]);
+        replica.assert_statements(&[
+            r#"SELECT "shows"."id", "shows"."title" FROM "shows" WHERE "shows"."id" = $1 LIMIT $2"#,
+        ]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,399 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use async_trait::async_trait;
+use nakago::{provider, Inject, Provider, Tag};
+use nakago_axum::utils::{CursorArgs, CursorResponse, ManyResponse, Ordering};
+use nakago_derive::Provider;
+use sea_orm::{
+    sea_query::{IntoValueTuple, ValueTuple},
+    ActiveModelBehavior, ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, DbErr,
+    EntityTrait, FromQueryResult, IntoActiveModel, IntoIdentity, Iterable, PaginatorTrait,
+    PrimaryKeyToColumn, PrimaryKeyTrait, QueryFilter, QueryOrder, Select,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{cursor, Connection, Transaction};
+
+/// The primary key value type for an Entity
+pub type PrimaryKeyValue<E> = <<E as EntityTrait>::PrimaryKey as PrimaryKeyTrait>::ValueType;
+
+/// Conditions that narrow down a query for an Entity, usually implemented for a GraphQL
+/// condition input
+pub trait Filter<E: EntityTrait> {
+    /// Add the conditions to the query
+    fn apply(self, query: Select<E>) -> Select<E>;
+}
+
+impl<E: EntityTrait> Filter<E> for Condition {
+    fn apply(self, query: Select<E>) -> Select<E> {
+        query.filter(self)
+    }
+}
+
+/// A Condition that matches any of the given primary key values
+pub fn ids_condition<E, K>(ids: impl IntoIterator<Item = K>) -> Condition
+where
+    E: EntityTrait,
+    K: Into<PrimaryKeyValue<E>>,
+{
+    let mut condition = Condition::any();
+
+    for id in ids {
+        let mut keys = E::PrimaryKey::iter().map(PrimaryKeyToColumn::into_column);
+
+        condition = match id.into().into_value_tuple() {
+            ValueTuple::One(value) => match keys.next() {
+                Some(column) => condition.add(column.eq(value)),
+                None => condition,
+            },
+            values => condition.add(
+                keys.zip(values)
+                    .fold(Condition::all(), |all, (column, value)| {
+                        all.add(column.eq(value))
+                    }),
+            ),
+        };
+    }
+
+    condition
+}
+
+/// A generic Repository with the common CRUD operations for an Entity, so that domain services
+/// can focus on business logic
+pub struct Repository<E: EntityTrait> {
+    db: Connection,
+    _phantom: PhantomData<fn() -> E>,
+}
+
+// Implement manually rather than deriving, to avoid requiring Clone for the Entity
+impl<E: EntityTrait> Clone for Repository<E> {
+    fn clone(&self) -> Self {
+        Self {
+            db: self.db.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<E: EntityTrait> Repository<E>
+where
+    E::Model: FromQueryResult + Sync,
+{
+    /// Create a new Repository with the given Connection
+    pub fn new(db: impl Into<Connection>) -> Self {
+        Self {
+            db: db.into(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Get a copy of the Repository that uses the given request Transaction, keeping the
+    /// instrumentation if its Connection is Instrumented
+    pub fn with_transaction(&self, txn: Transaction) -> Self {
+        Self::new(self.db.with_transaction(txn))
+    }
+
+    /// The Connection used by the Repository, for queries that it doesn't cover
+    pub fn connection(&self) -> &Connection {
+        &self.db
+    }
+
+    /// Get an individual Model by id
+    pub async fn get<K>(&self, id: K) -> Result<Option<E::Model>, DbErr>
+    where
+        K: Into<PrimaryKeyValue<E>>,
+    {
+        E::find_by_id(id).one(&self.db).await
+    }
+
+    /// Get a list of Models matching the given ids
+    pub async fn get_by_ids<K>(&self, ids: Vec<K>) -> Result<Vec<E::Model>, DbErr>
+    where
+        K: Into<PrimaryKeyValue<E>>,
+    {
+        E::find()
+            .filter(ids_condition::<E, K>(ids))
+            .all(&self.db)
+            .await
+    }
+
+    /// Get multiple Models matching the given conditions, in the given order, fetching a single
+    /// page if a page size is given
+    pub async fn get_many<F, O>(
+        &self,
+        condition: Option<F>,
+        order_by: Option<Vec<O>>,
+        page: Option<u64>,
+        page_size: Option<u64>,
+    ) -> Result<ManyResponse<E::Model>, DbErr>
+    where
+        F: Filter<E>,
+        O: Into<Ordering<E::Column>>,
+    {
+        let page_num = page.unwrap_or(1);
+
+        let mut query = E::find();
+
+        if let Some(condition) = condition {
+            query = condition.apply(query);
+        }
+
+        for order in order_by.unwrap_or_default() {
+            query = match order.into() {
+                Ordering::Asc(column) => query.order_by_asc(column),
+                Ordering::Desc(column) => query.order_by_desc(column),
+            };
+        }
+
+        let (data, total) = if let Some(page_size) = page_size {
+            let paginator = query.paginate(&self.db, page_size);
+            let total = paginator.num_items().await?;
+            let data = paginator.fetch_page(page_num.saturating_sub(1)).await?;
+
+            (data, total)
+        } else {
+            let data = query.all(&self.db).await?;
+            let total = data.len().try_into().unwrap_or(0);
+
+            (data, total)
+        };
+
+        Ok(ManyResponse::new(data, total, page_num, page_size))
+    }
+
+    /// Get a page of Models matching the given conditions with cursor pagination. See
+    /// `cursor::paginate` for how the order columns and the cursor key fit together.
+    pub async fn get_page<F, I, K, KF>(
+        &self,
+        condition: Option<F>,
+        order_by: Ordering<I>,
+        key: KF,
+        args: &CursorArgs,
+    ) -> Result<CursorResponse<E::Model>, cursor::PaginateError>
+    where
+        F: Filter<E>,
+        I: IntoIdentity,
+        K: Serialize + DeserializeOwned + IntoValueTuple,
+        KF: Fn(&E::Model) -> K,
+    {
+        let mut query = E::find();
+
+        if let Some(condition) = condition {
+            query = condition.apply(query);
+        }
+
+        cursor::paginate(&self.db, query, order_by, key, args).await
+    }
+
+    /// Insert a new Model from the given ActiveModel
+    pub async fn create<A>(&self, model: A) -> Result<E::Model, DbErr>
+    where
+        A: ActiveModelTrait<Entity = E> + ActiveModelBehavior + Send,
+        E::Model: IntoActiveModel<A>,
+    {
+        model.insert(&self.db).await
+    }
+
+    /// Save the changed fields of the given ActiveModel
+    pub async fn update<A>(&self, model: A) -> Result<E::Model, DbErr>
+    where
+        A: ActiveModelTrait<Entity = E> + ActiveModelBehavior + Send,
+        E::Model: IntoActiveModel<A>,
+    {
+        model.update(&self.db).await
+    }
+
+    /// Delete a Model by id, returning the number of rows deleted
+    pub async fn delete<K>(&self, id: K) -> Result<u64, DbErr>
+    where
+        K: Into<PrimaryKeyValue<E>>,
+    {
+        let result = E::delete_by_id(id).exec(&self.db).await?;
+
+        Ok(result.rows_affected)
+    }
+}
+
+/// Provide a Repository for an Entity, using the shared DatabaseConnection pool
+pub struct Provide<E: EntityTrait> {
+    connection_tag: Option<&'static Tag<DatabaseConnection>>,
+    _phantom: PhantomData<fn() -> E>,
+}
+
+// Implement manually rather than deriving, to avoid requiring Default for the Entity
+impl<E: EntityTrait> Default for Provide<E> {
+    fn default() -> Self {
+        Self {
+            connection_tag: None,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<E: EntityTrait> Provide<E> {
+    /// Create a new instance of Provide
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the DatabaseConnection Tag for this instance
+    pub fn with_connection_tag(self, connection_tag: &'static Tag<DatabaseConnection>) -> Self {
+        Self {
+            connection_tag: Some(connection_tag),
+            ..self
+        }
+    }
+}
+
+#[Provider]
+#[async_trait]
+impl<E: EntityTrait> Provider<Repository<E>> for Provide<E>
+where
+    E::Model: FromQueryResult + Sync,
+{
+    async fn provide(self: Arc<Self>, i: Inject) -> provider::Result<Arc<Repository<E>>> {
+        let db = if let Some(tag) = self.connection_tag {
+            i.get_tag(tag).await?
+        } else {
+            i.get::<DatabaseConnection>().await?
+        };
+
+        Ok(Arc::new(Repository::new(db)))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use pretty_assertions::assert_eq;
+    use sea_orm::{entity::prelude::*, DbBackend, MockExecResult, Set};
+
+    use super::*;
+    use crate::{
+        instrument,
+        mock::{self, MockHandle},
+        Instrumented,
+    };
+
+    pub(crate) mod shows {
+        use sea_orm::entity::prelude::*;
+
+        #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+        #[sea_orm(table_name = "shows")]
+        pub struct Model {
+            #[sea_orm(primary_key, auto_increment = false)]
+            pub id: String,
+            pub title: String,
+        }
+
+        #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+        pub enum Relation {}
+
+        impl ActiveModelBehavior for ActiveModel {}
+    }
+
+    struct TitleFilter(String);
+
+    impl Filter<shows::Entity> for TitleFilter {
+        fn apply(self, query: Select<shows::Entity>) -> Select<shows::Entity> {
+            query.filter(shows::Column::Title.eq(self.0))
+        }
+    }
+
+    fn show(id: &str, title: &str) -> shows::Model {
+        shows::Model {
+            id: id.to_string(),
+            title: title.to_string(),
+        }
+    }
+
+    async fn setup() -> anyhow::Result<(MockHandle, Arc<Repository<shows::Entity>>)> {
+        let i = Inject::default();
+        let mock = MockHandle::new(DbBackend::Postgres);
+
+        i.provide::<DatabaseConnection>(mock::Provide::new(mock.clone()))
+            .await?;
+        i.provide::<Repository<shows::Entity>>(Provide::default())
+            .await?;
+
+        let repo = i.get::<Repository<shows::Entity>>().await?;
+
+        Ok((mock, repo))
+    }
+
+    #[tokio::test]
+    async fn test_repository_get_many() -> anyhow::Result<()> {
+        let (mock, repo) = setup().await?;
+
+        mock.append_query_results([vec![show("1", "Show")]])
+            .append_query_results([vec![show("1", "Show"), show("2", "Other")]]);
+
+        let shows = repo
+            .get_many(
+                Some(TitleFilter("Show".to_string())),
+                Some(vec![Ordering::Desc(shows::Column::Title)]),
+                None,
+                None,
+            )
+            .await?;
+
+        assert_eq!(1, shows.count);
+        assert_eq!(vec![show("1", "Show")], shows.data);
+
+        let shows = repo.get_by_ids(vec!["1", "2"]).await?;
+        assert_eq!(2, shows.len());
+
+        mock.assert_statements(&[
+            r#"SELECT "shows"."id", "shows"."title" FROM "shows" WHERE "shows"."title" = $1 ORDER BY "shows"."title" DESC"#,
+            r#"SELECT "shows"."id", "shows"."title" FROM "shows" WHERE "shows"."id" = $1 OR "shows"."id" = $2"#,
+        ]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_repository_write() -> anyhow::Result<()> {
+        let (mock, repo) = setup().await?;
+
+        mock.append_query_results([vec![show("1", "Show")]])
+            .append_exec_results([MockExecResult {
+                last_insert_id: 0,
+                rows_affected: 1,
+            }]);
+
+        let created = repo
+            .create(shows::ActiveModel {
+                id: Set("1".to_string()),
+                title: Set("Show".to_string()),
+            })
+            .await?;
+
+        assert_eq!(show("1", "Show"), created);
+        assert_eq!(1, repo.delete("1").await?);
+
+        mock.assert_statements(&[
+            r#"INSERT INTO "shows" ("id", "title") VALUES ($1, $2) RETURNING "id", "title""#,
+            r#"DELETE FROM "shows" WHERE "shows"."id" = $1"#,
+        ]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_repository_with_transaction() -> anyhow::Result<()> {
+        let mock = MockHandle::new(DbBackend::Postgres);
+        let db = Arc::new(mock.connection());
+
+        let instrumented = Instrumented::new(db.clone(), instrument::Options::default());
+        let repo = Repository::<shows::Entity>::new(instrumented);
+
+        // The request Transaction keeps the instrumentation
+        let scoped = repo.with_transaction(Transaction::new(db.clone()));
+        assert!(matches!(scoped.connection(), Connection::Instrumented(_)));
+
+        let repo = Repository::<shows::Entity>::new(db.clone());
+        let scoped = repo.with_transaction(Transaction::new(db));
+        assert!(matches!(scoped.connection(), Connection::Transaction(_)));
+
+        Ok(())
+    }
+}
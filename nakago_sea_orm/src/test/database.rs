@@ -0,0 +1,218 @@
+use std::{fs, path::PathBuf, sync::Arc};
+
+use async_trait::async_trait;
+use nakago::{provider, Inject, Provider, Tag};
+use nakago_derive::Provider;
+use sea_orm::{ConnectOptions, ConnectionTrait, Database, DatabaseConnection, DbErr};
+
+/// An isolated SQLite database for a single test, so that service tests can run in parallel
+/// without a database server.
+///
+/// A temp-file database is removed when the TestDatabase is dropped. An in-memory database lives
+/// on its single pooled connection, so it is gone once the last reference to the connection is
+/// dropped.
+#[derive(Debug)]
+pub struct TestDatabase {
+    db: Arc<DatabaseConnection>,
+    path: Option<PathBuf>,
+}
+
+impl TestDatabase {
+    /// Create an in-memory database. The pool is limited to a single connection, since each
+    /// SQLite connection to `:memory:` opens a separate database.
+    pub async fn memory() -> Result<Self, DbErr> {
+        let mut options = ConnectOptions::new("sqlite::memory:");
+        options
+            .min_connections(1)
+            .max_connections(1)
+            .sqlx_logging(false);
+
+        Ok(Self {
+            db: Arc::new(Database::connect(options).await?),
+            path: None,
+        })
+    }
+
+    /// Create a database in a new file in the temp directory, which allows concurrent
+    /// connections and transactions
+    pub async fn temp_file() -> Result<Self, DbErr> {
+        let path = std::env::temp_dir().join(format!("nakago-test-{}.db", ulid::Ulid::new()));
+
+        let mut options = ConnectOptions::new(format!("sqlite://{}?mode=rwc", path.display()));
+        options.sqlx_logging(false);
+
+        Ok(Self {
+            db: Arc::new(Database::connect(options).await?),
+            path: Some(path),
+        })
+    }
+
+    /// The database connection
+    pub fn connection(&self) -> Arc<DatabaseConnection> {
+        self.db.clone()
+    }
+
+    /// The path to the database file, or None if it is in memory
+    pub fn path(&self) -> Option<&PathBuf> {
+        self.path.as_ref()
+    }
+
+    /// Run the given SQL statements, such as a schema for tests that don't use a Migrator
+    pub async fn execute(&self, sql: &str) -> Result<(), DbErr> {
+        self.db.execute_unprepared(sql).await?;
+
+        Ok(())
+    }
+
+    /// Apply every migration from the given Migrator
+    #[cfg(feature = "migrate")]
+    pub async fn migrate<M: sea_orm_migration::MigratorTrait>(&self) -> Result<(), DbErr> {
+        M::up(&*self.db, None).await
+    }
+
+    /// Register the connection in the container under the DatabaseConnection type
+    pub async fn inject(&self, i: &Inject) -> nakago::Result<()> {
+        i.provide::<DatabaseConnection>(Shared(self.db.clone()))
+            .await
+    }
+
+    /// Register the connection in the container under the given Tag, such as `CONNECTION`
+    pub async fn inject_tag(
+        &self,
+        i: &Inject,
+        tag: &'static Tag<DatabaseConnection>,
+    ) -> nakago::Result<()> {
+        i.provide_tag(tag, Shared(self.db.clone())).await
+    }
+}
+
+// Provide the shared connection, since a DatabaseConnection can't be cloned when mocking is enabled
+struct Shared(Arc<DatabaseConnection>);
+
+#[Provider]
+#[async_trait]
+impl Provider<DatabaseConnection> for Shared {
+    async fn provide(self: Arc<Self>, _i: Inject) -> provider::Result<Arc<DatabaseConnection>> {
+        Ok(self.0.clone())
+    }
+}
+
+impl Drop for TestDatabase {
+    fn drop(&mut self) {
+        if let Some(path) = &self.path {
+            // SQLite may leave journal files alongside the database
+            for suffix in ["", "-journal", "-wal", "-shm"] {
+                let mut file = path.clone().into_os_string();
+                file.push(suffix);
+
+                let _ = fs::remove_file(file);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use pretty_assertions::assert_eq;
+    use sea_orm::Statement;
+
+    use crate::CONNECTION;
+
+    use super::*;
+
+    async fn count(db: &DatabaseConnection) -> anyhow::Result<i64> {
+        let row = db
+            .query_one(Statement::from_string(
+                db.get_database_backend(),
+                "SELECT COUNT(*) AS count FROM shows",
+            ))
+            .await?
+            .expect("no count returned");
+
+        Ok(row.try_get("", "count")?)
+    }
+
+    #[tokio::test]
+    async fn test_memory_database() -> anyhow::Result<()> {
+        let i = Inject::default();
+
+        let test_db = TestDatabase::memory().await?;
+        test_db
+            .execute("CREATE TABLE shows (title TEXT NOT NULL); INSERT INTO shows VALUES ('Show');")
+            .await?;
+
+        test_db.inject(&i).await?;
+
+        // The injected connection shares the same database
+        let db = i.get::<DatabaseConnection>().await?;
+        assert_eq!(1, count(&db).await?);
+
+        // Each TestDatabase is isolated
+        let other = TestDatabase::memory().await?;
+        assert!(count(&other.connection()).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_temp_file_database() -> anyhow::Result<()> {
+        let i = Inject::default();
+
+        let test_db = TestDatabase::temp_file().await?;
+        test_db
+            .execute("CREATE TABLE shows (title TEXT NOT NULL)")
+            .await?;
+
+        test_db.inject_tag(&i, &CONNECTION).await?;
+
+        let db = i.get_tag(&CONNECTION).await?;
+        db.execute_unprepared("INSERT INTO shows VALUES ('Show')")
+            .await?;
+        assert_eq!(1, count(&test_db.connection()).await?);
+
+        // The file is removed when the TestDatabase is dropped
+        let path = test_db.path().cloned().expect("no path for a temp file");
+        assert!(path.exists());
+
+        drop(test_db);
+        assert!(!path.exists());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "migrate")]
+    #[tokio::test]
+    async fn test_migrate() -> anyhow::Result<()> {
+        use sea_orm::Set;
+
+        use crate::{
+            migrate::test::Migrator,
+            repository::{self, test::shows},
+            Repository,
+        };
+
+        let i = Inject::default();
+
+        let test_db = TestDatabase::memory().await?;
+        test_db.migrate::<Migrator>().await?;
+        test_db.inject(&i).await?;
+
+        // Services provided from the container run against the migrated schema
+        i.provide::<Repository<shows::Entity>>(repository::Provide::default())
+            .await?;
+
+        let repo = i.get::<Repository<shows::Entity>>().await?;
+
+        let created = repo
+            .create(shows::ActiveModel {
+                id: Set("1".to_string()),
+                title: Set("Show".to_string()),
+            })
+            .await?;
+
+        assert_eq!(Some(created), repo.get("1").await?);
+        assert_eq!(1, count(&test_db.connection()).await?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,4 @@
+/// Ephemeral SQLite databases
+pub mod database;
+
+pub use database::TestDatabase;
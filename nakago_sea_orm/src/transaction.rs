@@ -0,0 +1,399 @@
+use std::{
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use async_trait::async_trait;
+use axum::{
+    extract::{FromRequestParts, OptionalFromRequestParts, Request, State},
+    http::{request::Parts, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbBackend, DbErr, ExecResult,
+    QueryResult, Statement, TransactionTrait,
+};
+use tokio::sync::{MappedMutexGuard, Mutex, MutexGuard};
+
+/// A request-scoped database Transaction, opened by the transaction `middleware` and shared by
+/// every handler and service that takes part in the request.
+///
+/// The database transaction is begun lazily, when the first statement is sent, so requests that
+/// never use it don't hold a pooled connection. It is committed when the response is successful,
+/// and rolled back for a client or server error response, when `set_rollback_only` has been
+/// called, or when the handler panics. Handles that outlive the request fail to run statements
+/// once it has finished.
+#[derive(Clone, Debug)]
+pub struct Transaction(Arc<Inner>);
+
+#[derive(Debug)]
+struct Inner {
+    db: Arc<DatabaseConnection>,
+    phase: Mutex<Phase>,
+    begun: AtomicBool,
+    rollback_only: AtomicBool,
+}
+
+#[derive(Debug)]
+enum Phase {
+    Pending,
+    Open(DatabaseTransaction),
+    Finished,
+}
+
+impl Transaction {
+    /// Create a Transaction on the given connection, which is begun when it is first used
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        Self(Arc::new(Inner {
+            db,
+            phase: Mutex::new(Phase::Pending),
+            begun: AtomicBool::new(false),
+            rollback_only: AtomicBool::new(false),
+        }))
+    }
+
+    /// Begin a new Transaction on the given connection right away
+    pub async fn begin(db: &Arc<DatabaseConnection>) -> Result<Self, DbErr> {
+        let txn = Self::new(db.clone());
+        drop(txn.txn().await?);
+
+        Ok(txn)
+    }
+
+    /// Whether the database transaction has been begun
+    pub fn is_begun(&self) -> bool {
+        self.0.begun.load(Ordering::SeqCst)
+    }
+
+    /// Mark the Transaction so that it is rolled back rather than committed, such as when a GraphQL
+    /// response has errors but is still sent with a successful status
+    pub fn set_rollback_only(&self) {
+        self.0.rollback_only.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether the Transaction has been marked to be rolled back
+    pub fn is_rollback_only(&self) -> bool {
+        self.0.rollback_only.load(Ordering::SeqCst)
+    }
+
+    /// Commit the Transaction, or roll it back if it was marked with `set_rollback_only`. Does
+    /// nothing if it was never begun. Other handles may still be held, but statements sent through
+    /// them afterwards fail. Fails if the Transaction has already finished.
+    pub async fn finish(&self) -> Result<(), DbErr> {
+        let phase = mem::replace(&mut *self.0.phase.lock().await, Phase::Finished);
+
+        match phase {
+            Phase::Pending => Ok(()),
+            Phase::Open(txn) if self.is_rollback_only() => txn.rollback().await,
+            Phase::Open(txn) => txn.commit().await,
+            Phase::Finished => Err(finished()),
+        }
+    }
+
+    // The database transaction, begun on first use. The lock is held while a statement runs.
+    async fn txn(&self) -> Result<MappedMutexGuard<'_, DatabaseTransaction>, DbErr> {
+        let mut phase = self.0.phase.lock().await;
+
+        if matches!(*phase, Phase::Pending) {
+            *phase = Phase::Open(self.0.db.begin().await?);
+            self.0.begun.store(true, Ordering::SeqCst);
+        }
+
+        MutexGuard::try_map(phase, |phase| match phase {
+            Phase::Open(txn) => Some(txn),
+            Phase::Pending | Phase::Finished => None,
+        })
+        .map_err(|_state| finished())
+    }
+}
+
+fn finished() -> DbErr {
+    DbErr::Custom("the request transaction has already finished".to_string())
+}
+
+#[async_trait]
+impl ConnectionTrait for Transaction {
+    fn get_database_backend(&self) -> DbBackend {
+        self.0.db.get_database_backend()
+    }
+
+    async fn execute(&self, stmt: Statement) -> Result<ExecResult, DbErr> {
+        self.txn().await?.execute(stmt).await
+    }
+
+    async fn execute_unprepared(&self, sql: &str) -> Result<ExecResult, DbErr> {
+        self.txn().await?.execute_unprepared(sql).await
+    }
+
+    async fn query_one(&self, stmt: Statement) -> Result<Option<QueryResult>, DbErr> {
+        self.txn().await?.query_one(stmt).await
+    }
+
+    async fn query_all(&self, stmt: Statement) -> Result<Vec<QueryResult>, DbErr> {
+        self.txn().await?.query_all(stmt).await
+    }
+
+    fn is_mock_connection(&self) -> bool {
+        self.0.db.is_mock_connection()
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Transaction {
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<Transaction>().cloned().ok_or((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "The transaction middleware is not installed for this route",
+        ))
+    }
+}
+
+impl<S: Send + Sync> OptionalFromRequestParts<S> for Transaction {
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        Ok(parts.extensions.get::<Transaction>().cloned())
+    }
+}
+
+/// An Axum middleware that provides a Transaction for each request, for use with
+/// `axum::middleware::from_fn_with_state` and the `DatabaseConnection` from the container:
+///
+/// ```ignore
+/// let db = i.get::<DatabaseConnection>().await?;
+///
+/// router.layer(from_fn_with_state(db, transaction::middleware))
+/// ```
+pub async fn middleware(
+    State(db): State<Arc<DatabaseConnection>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let txn = Transaction::new(db);
+
+    request.extensions_mut().insert(txn.clone());
+
+    // If the handler panics, the Transaction is dropped during the unwind and rolled back
+    let response = next.run(request).await;
+
+    let status = response.status();
+    if status.is_client_error() || status.is_server_error() {
+        txn.set_rollback_only();
+    }
+
+    match txn.finish().await {
+        Ok(()) => response,
+        Err(err) => error_response("finish", &err),
+    }
+}
+
+fn error_response(action: &str, err: &DbErr) -> Response {
+    log::error!("Unable to {action} the request transaction: {err}");
+
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Unable to complete the request transaction",
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use axum::{body::Body, extract::Path, middleware::from_fn_with_state, routing::post, Router};
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    use sea_orm::{ConnectOptions, Database};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    async fn setup() -> anyhow::Result<(Arc<DatabaseConnection>, Router)> {
+        let path = std::env::temp_dir().join(format!("nakago-txn-{}.db", ulid::Ulid::new()));
+
+        // A single pooled connection, so a Transaction begun too early starves the handler
+        let mut options = ConnectOptions::new(format!("sqlite://{}?mode=rwc", path.display()));
+        options
+            .max_connections(1)
+            .acquire_timeout(Duration::from_secs(1));
+
+        let db = Database::connect(options).await?;
+
+        db.execute_unprepared("CREATE TABLE shows (title TEXT NOT NULL)")
+            .await?;
+
+        let db = Arc::new(db);
+
+        let router = Router::new()
+            .route("/{status}", post(insert))
+            .route(
+                "/read",
+                post({
+                    let db = db.clone();
+                    move |txn: Transaction| read(db, txn)
+                }),
+            )
+            .layer(from_fn_with_state(db.clone(), middleware));
+
+        Ok((db, router))
+    }
+
+    async fn insert(txn: Transaction, Path(status): Path<u16>) -> StatusCode {
+        txn.execute_unprepared("INSERT INTO shows (title) VALUES ('Show')")
+            .await
+            .expect("insert failed");
+
+        if status == 999 {
+            panic!("the handler panicked");
+        }
+
+        StatusCode::from_u16(status).unwrap_or(StatusCode::OK)
+    }
+
+    async fn read(db: Arc<DatabaseConnection>, txn: Transaction) -> StatusCode {
+        match count(&*db).await {
+            Ok(_) if !txn.is_begun() => StatusCode::OK,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    async fn count(db: &impl ConnectionTrait) -> anyhow::Result<i64> {
+        let row = db
+            .query_one(Statement::from_string(
+                db.get_database_backend(),
+                "SELECT COUNT(*) AS count FROM shows",
+            ))
+            .await?
+            .expect("no count returned");
+
+        Ok(row.try_get("", "count")?)
+    }
+
+    async fn send(router: Router, uri: &str) -> anyhow::Result<StatusCode> {
+        let request = Request::post(uri).body(Body::empty())?;
+
+        Ok(router.oneshot(request).await?.status())
+    }
+
+    #[tokio::test]
+    async fn test_transaction_commit() -> anyhow::Result<()> {
+        let (db, router) = setup().await?;
+
+        assert_eq!(StatusCode::CREATED, send(router, "/201").await?);
+        assert_eq!(1, count(&*db).await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transaction_rollback_on_error() -> anyhow::Result<()> {
+        let (db, router) = setup().await?;
+
+        assert_eq!(StatusCode::BAD_REQUEST, send(router.clone(), "/400").await?);
+        assert_eq!(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            send(router, "/500").await?
+        );
+        assert_eq!(0, count(&*db).await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transaction_rollback_on_panic() -> anyhow::Result<()> {
+        let (db, router) = setup().await?;
+
+        let result = tokio::spawn(send(router, "/999")).await;
+        assert!(result.is_err_and(|err| err.is_panic()));
+
+        assert_eq!(0, count(&*db).await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transaction_lazy() -> anyhow::Result<()> {
+        let (db, router) = setup().await?;
+
+        // The handler reads through the pool without using the Transaction
+        assert_eq!(StatusCode::OK, send(router, "/read").await?);
+
+        let txn = Transaction::new(db.clone());
+        assert!(!txn.is_begun());
+
+        assert_eq!(0, count(&txn).await?);
+        assert!(txn.is_begun());
+
+        txn.finish().await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transaction_rollback_only() -> anyhow::Result<()> {
+        let (db, _router) = setup().await?;
+
+        let txn = Transaction::begin(&db).await?;
+        txn.execute_unprepared("INSERT INTO shows (title) VALUES ('Show')")
+            .await?;
+
+        let handle = txn.clone();
+        handle.set_rollback_only();
+
+        // Finishing doesn't wait for the other handle to be dropped
+        txn.finish().await?;
+
+        assert!(handle
+            .execute_unprepared("INSERT INTO shows (title) VALUES ('Show')")
+            .await
+            .is_err());
+        assert!(txn.finish().await.is_err());
+
+        assert_eq!(0, count(&*db).await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transaction_handle_outlives_handler() -> anyhow::Result<()> {
+        let (db, _router) = setup().await?;
+
+        let stash = Arc::new(std::sync::Mutex::new(None::<Transaction>));
+
+        let router = Router::new()
+            .route(
+                "/stash",
+                post({
+                    let stash = stash.clone();
+                    move |txn: Transaction| async move {
+                        txn.execute_unprepared("INSERT INTO shows (title) VALUES ('Show')")
+                            .await
+                            .expect("insert failed");
+
+                        stash.lock().expect("stash poisoned").replace(txn);
+
+                        StatusCode::CREATED
+                    }
+                }),
+            )
+            .layer(from_fn_with_state(db.clone(), middleware));
+
+        assert_eq!(StatusCode::CREATED, send(router, "/stash").await?);
+        assert_eq!(1, count(&*db).await?);
+
+        let handle = stash.lock().expect("stash poisoned").take();
+        let handle = handle.expect("no handle stashed");
+
+        assert!(count(&handle).await.is_err());
+
+        Ok(())
+    }
+}
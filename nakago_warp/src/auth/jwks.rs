@@ -5,9 +5,9 @@ use biscuit::{
     jwk::{AlgorithmParameters, JWKSet, JWK},
     jws::Secret,
 };
-use nakago::{self, provider, Inject, Provider, Tag};
+use nakago::{self, provider, to_provider_error, Inject, Provider, Tag};
 use nakago_derive::Provider;
-use nakago_figment::FromRef;
+use nakago_figment::{named, FromRef, Named};
 use thiserror::Error;
 
 use super::Config;
@@ -130,3 +130,129 @@ where
         Ok(Arc::new(key_set))
     }
 }
+
+/// Provide the Json Web Key Set for a named instance in a map-shaped auth section, such as
+/// `[auth.partner]`
+pub struct ProvideNamed<C: nakago_figment::Config> {
+    name: String,
+    config_tag: Option<&'static Tag<C>>,
+}
+
+impl<C: nakago_figment::Config> ProvideNamed<C> {
+    /// Create a new instance of ProvideNamed for the given instance name
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            config_tag: None,
+        }
+    }
+
+    /// Set the config Tag for this instance
+    pub fn with_config_tag(self, config_tag: &'static Tag<C>) -> Self {
+        Self {
+            config_tag: Some(config_tag),
+            ..self
+        }
+    }
+}
+
+#[Provider]
+#[async_trait]
+impl<C: nakago_figment::Config> Provider<JWKSet<biscuit::Empty>> for ProvideNamed<C>
+where
+    Named<Config>: FromRef<C>,
+{
+    async fn provide(self: Arc<Self>, i: Inject) -> provider::Result<Arc<JWKSet<biscuit::Empty>>> {
+        let auth: Config = named::instance(&i, self.config_tag, &self.name).await?;
+
+        let key_set = Client::new(auth)
+            .get_key_set()
+            .await
+            .map_err(to_provider_error)?;
+
+        Ok(Arc::new(key_set))
+    }
+}
+
+/// Provide the Json Web Key Set for each of the given auth instances, under the `named::tag` for
+/// each instance name, such as `named::tag::<Jwks>("partner")`
+pub async fn provide_named<C: nakago_figment::Config>(
+    i: &Inject,
+    names: &[&str],
+) -> nakago::Result<()>
+where
+    Named<Config>: FromRef<C>,
+{
+    for name in names {
+        i.provide_tag(named::tag::<Jwks>(name), ProvideNamed::<C>::new(name))
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use nakago_derive::Config;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use warp::Filter;
+
+    use super::*;
+
+    #[derive(Clone, Debug, Default, Serialize, Deserialize, Config)]
+    pub struct Auths {
+        pub auth: Named<Config>,
+    }
+
+    // Serve a JWKS for each instance name, with a single key identified by the name
+    fn serve() -> String {
+        let routes = warp::path!(String / ".well-known" / "jwks.json").map(|name: String| {
+            warp::reply::json(&json!({ "keys": [{ "kty": "oct", "kid": name, "k": "c2VjcmV0" }] }))
+        });
+
+        let (addr, server) = warp::serve(routes).bind_ephemeral(([127, 0, 0, 1], 0));
+
+        tokio::spawn(server);
+
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn test_provide_named_success() -> anyhow::Result<()> {
+        let i = Inject::default();
+        let url = serve();
+
+        let instance = |name: &str| Config {
+            url: format!("{url}/{name}"),
+            ..Config::default()
+        };
+
+        let auth = Named::default()
+            .with("primary", instance("primary"))
+            .with("partner", instance("partner"));
+
+        i.inject(Auths { auth }).await?;
+
+        provide_named::<Auths>(&i, &["primary", "partner"]).await?;
+
+        let primary = i.get_tag(named::tag::<Jwks>("primary")).await?;
+        assert!(primary.find("primary").is_some());
+        assert!(primary.find("partner").is_none());
+
+        let partner = i.get_tag(named::tag::<Jwks>("partner")).await?;
+        assert!(partner.find("partner").is_some());
+
+        // An unknown instance name fails
+        i.provide_tag(
+            named::tag::<Jwks>("missing"),
+            ProvideNamed::<Auths>::new("missing"),
+        )
+        .await?;
+
+        let result = i.get_tag(named::tag::<Jwks>("missing")).await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+}